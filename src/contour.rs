@@ -1,41 +1,342 @@
-use std::ops::{Index, Sub, Add};
+use std::{
+    str::FromStr,
+    ops::{Index, Sub, Add, Mul, Neg}
+};
 
-use super::FloatImage;
+use super::{FloatImage, edge_graph};
 
-mod simplify;
+pub mod simplify;
+mod marching;
+mod dedup;
+pub mod join;
+pub mod smooth;
+pub mod corners;
+pub mod primitives;
+pub mod bezier;
+pub mod potrace;
+pub mod clip;
 
 
 #[derive(Debug, Clone)]
 pub struct Curve
 {
-    points: Vec<Pos>
+    points: Vec<Pos>,
+    closed: bool
 }
 
 impl Curve
 {
     pub fn new(points: Vec<Pos>) -> Self
     {
-        Self{points}
+        Self{points, closed: false}
     }
 
-    pub fn append(&mut self, other: &mut Self)
+    // the last point connects back to the first one
+    pub fn new_closed(points: Vec<Pos>) -> Self
     {
-        self.points.append(&mut other.points);
+        Self{points, closed: true}
+    }
+
+    pub fn from_points(points: Vec<Pos>, closed: bool) -> Self
+    {
+        Self{points, closed}
+    }
+
+    pub fn points(&self) -> &[Pos]
+    {
+        &self.points
+    }
+
+    pub fn is_closed(&self) -> bool
+    {
+        self.closed
+    }
+
+    pub fn start(&self) -> Pos
+    {
+        self.points[0]
     }
 
-    pub fn part(&self, start: usize, end: usize) -> Self
+    // closed curves end where they started
+    pub fn end(&self) -> Pos
     {
-        Self::new(self.points[start..end].to_vec())
+        if self.closed
+        {
+            self.points[0]
+        } else
+        {
+            self.points[self.points.len() - 1]
+        }
+    }
+
+    pub fn reverse(&mut self)
+    {
+        if self.closed
+        {
+            self.points[1..].reverse();
+        } else
+        {
+            self.points.reverse();
+        }
+    }
+
+    pub fn append(&mut self, other: &mut Self)
+    {
+        self.points.append(&mut other.points);
     }
 
     pub fn curve_length(&self) -> f64
     {
-        self.points.iter().fold((self.points[0], 0.0), |(previous, acc), current|
+        let (last, length) = self.points.iter().fold((self.points[0], 0.0), |(previous, acc), current|
         {
             let line_length = (*current - previous).magnitude();
 
             (*current, acc + line_length)
-        }).1
+        });
+
+        if self.closed
+        {
+            length + (self.points[0] - last).magnitude()
+        } else
+        {
+            length
+        }
+    }
+
+    // every line between two points, including the one back to the start of closed curves
+    fn segments(&self) -> impl Iterator<Item=(Pos, Pos)> + '_
+    {
+        let closing = self.closed.then(|| (self.points[self.points.len() - 1], self.points[0]));
+
+        self.points.windows(2).map(|pair| (pair[0], pair[1])).chain(closing)
+    }
+
+    // points evenly spaced along the curve around spacing apart, the ends stay put
+    pub fn resample(&self, spacing: f64) -> Self
+    {
+        let length = self.curve_length();
+        if self.points.len() < 2 || length == 0.0
+        {
+            return self.clone();
+        }
+
+        let min_pieces = if self.closed { 3 } else { 1 };
+        let pieces = ((length / spacing).round() as usize).max(min_pieces);
+        let step = length / pieces as f64;
+
+        // closed curves dont repeat their start at the end
+        let samples = if self.closed { pieces } else { pieces + 1 };
+
+        let segments = self.segments().collect::<Vec<_>>();
+
+        let mut current = 0;
+        let mut walked = 0.0;
+
+        let points = (0..samples).map(|sample|
+        {
+            let target = (sample as f64 * step).min(length);
+
+            loop
+            {
+                let (a, b) = segments[current];
+                let segment_length = (b - a).magnitude();
+
+                if walked + segment_length >= target || current + 1 == segments.len()
+                {
+                    let t = if segment_length > 0.0
+                    {
+                        ((target - walked) / segment_length).clamp(0.0, 1.0)
+                    } else
+                    {
+                        0.0
+                    };
+
+                    return a.lerp(b, t);
+                }
+
+                walked += segment_length;
+                current += 1;
+            }
+        }).collect();
+
+        Self{points, closed: self.closed}
+    }
+
+    // adds points along lines longer than max_length, the original points stay
+    pub fn subdivide(&self, max_length: f64) -> Self
+    {
+        if self.points.len() < 2
+        {
+            return self.clone();
+        }
+
+        let mut points = Vec::with_capacity(self.points.len());
+        for (a, b) in self.segments()
+        {
+            let pieces = ((b - a).magnitude() / max_length).ceil().max(1.0) as usize;
+
+            points.extend((0..pieces).map(|piece| a.lerp(b, piece as f64 / pieces as f64)));
+        }
+
+        if !self.closed
+        {
+            points.push(self.points[self.points.len() - 1]);
+        }
+
+        Self{points, closed: self.closed}
+    }
+
+    // smallest and largest corner of the box around every point
+    pub fn bounding_box(&self) -> (Pos, Pos)
+    {
+        self.points.iter().fold((self.points[0], self.points[0]), |(low, high), point|
+        {
+            (
+                Pos::new(low.x.min(point.x), low.y.min(point.y)),
+                Pos::new(high.x.max(point.x), high.y.max(point.y))
+            )
+        })
+    }
+
+    // shoelace formula as if the curve was closed, positive when going clockwise on screen
+    pub fn signed_area(&self) -> f64
+    {
+        let len = self.points.len();
+
+        (0..len).map(|index| self.points[index].cross(self.points[(index + 1) % len])).sum::<f64>() / 2.0
+    }
+
+    // center of the enclosed area for closed curves, center along the lines for open ones
+    pub fn centroid(&self) -> Pos
+    {
+        let area = self.signed_area();
+
+        if self.closed && area.abs() > f64::EPSILON
+        {
+            let len = self.points.len();
+
+            let sum = (0..len).fold(Pos::new(0.0, 0.0), |sum, index|
+            {
+                let (a, b) = (self.points[index], self.points[(index + 1) % len]);
+
+                sum + (a + b) * a.cross(b)
+            });
+
+            return sum * (1.0 / (6.0 * area));
+        }
+
+        let length = self.curve_length();
+        if length == 0.0
+        {
+            return self.points[0];
+        }
+
+        self.segments().fold(Pos::new(0.0, 0.0), |sum, (a, b)|
+        {
+            sum + a.lerp(b, 0.5) * ((b - a).magnitude() / length)
+        })
+    }
+
+    pub fn map(self, f: impl FnMut(Pos) -> Pos) -> Self
+    {
+        Self{points: self.points.into_iter().map(f).collect(), closed: self.closed}
+    }
+
+    // open curves split into two halves sharing the point at index
+    // closed curves have no second half, they come back as one open curve starting and ending at index
+    pub fn split_at(&self, index: usize) -> (Self, Option<Self>)
+    {
+        if self.closed
+        {
+            let mut points = self.points.clone();
+            points.rotate_left(index);
+            points.push(points[0]);
+
+            return (Self::new(points), None);
+        }
+
+        (Self::new(self.points[..=index].to_vec()), Some(Self::new(self.points[index..].to_vec())))
+    }
+
+    // the point a fraction t of the way along the curve and the segment its on
+    pub fn point_at(&self, t: f64) -> (usize, Pos)
+    {
+        let target = self.curve_length() * t.clamp(0.0, 1.0);
+
+        let mut walked = 0.0;
+        let segments = self.segments().collect::<Vec<_>>();
+
+        for (index, (a, b)) in segments.iter().copied().enumerate()
+        {
+            let length = (b - a).magnitude();
+
+            if walked + length >= target && length > 0.0
+            {
+                return (index, a.lerp(b, (target - walked) / length));
+            }
+
+            walked += length;
+        }
+
+        (segments.len().saturating_sub(1), self.end())
+    }
+
+    // splits a fraction t of the way along the curve, closed curves get opened at their start first
+    #[allow(dead_code)]
+    pub fn split_at_parameter(&self, t: f64) -> (Self, Self)
+    {
+        let mut points = self.points.clone();
+        if self.closed
+        {
+            points.push(points[0]);
+        }
+
+        if points.len() < 2
+        {
+            return (Self::new(points.clone()), Self::new(points));
+        }
+
+        let (index, point) = Self::new(points.clone()).point_at(t);
+
+        let mut first = points[..=index].to_vec();
+        if first.last() != Some(&point)
+        {
+            first.push(point);
+        }
+
+        // splitting right on a point shouldnt repeat it
+        let mut second = vec![point];
+        second.extend(points[(index + 1)..].iter().copied().skip_while(|next| *next == point));
+
+        (Self::new(first), Self::new(second))
+    }
+
+    // closest point on the curve to pos and the segment its on
+    pub fn nearest_point(&self, pos: Pos) -> (usize, Pos)
+    {
+        if self.points.len() < 2
+        {
+            return (0, self.points[0]);
+        }
+
+        self.segments().map(|(a, b)|
+        {
+            let line = b - a;
+            let length_squared = line.dot(line);
+
+            let t = if length_squared > 0.0
+            {
+                ((pos - a).dot(line) / length_squared).clamp(0.0, 1.0)
+            } else
+            {
+                0.0
+            };
+
+            a.lerp(b, t)
+        }).enumerate().min_by(|(_, a), (_, b)|
+        {
+            (*a - pos).magnitude().total_cmp(&(*b - pos).magnitude())
+        }).unwrap()
     }
 
     pub fn len(&self) -> usize
@@ -59,7 +360,27 @@ impl Index<usize> for Curve
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+// a unit square outline starting at the origin with a point every tenth of a side
+#[cfg(test)]
+pub fn test_square(closed: bool) -> Curve
+{
+    let points = (0..40).map(|index|
+    {
+        let (side, t) = (index / 10, (index % 10) as f64 / 10.0);
+
+        match side
+        {
+            0 => Pos::new(t, 0.0),
+            1 => Pos::new(1.0, t),
+            2 => Pos::new(1.0 - t, 1.0),
+            _ => Pos::new(0.0, 1.0 - t)
+        }
+    }).collect();
+
+    Curve::from_points(points, closed)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Pos
 {
     pub x: f64,
@@ -77,6 +398,56 @@ impl Pos
     {
         self.x.hypot(self.y)
     }
+
+    pub fn dot(self, other: Self) -> f64
+    {
+        self.x * other.x + self.y * other.y
+    }
+
+    // z of the 3d cross product, twice the signed area of the triangle they make
+    pub fn cross(self, other: Self) -> f64
+    {
+        self.x * other.y - self.y * other.x
+    }
+
+    // same direction with a length of 1, zero vectors stay zero
+    pub fn normalize(self) -> Self
+    {
+        let magnitude = self.magnitude();
+
+        if magnitude == 0.0
+        {
+            self
+        } else
+        {
+            self * (1.0 / magnitude)
+        }
+    }
+
+    pub fn lerp(self, other: Self, t: f64) -> Self
+    {
+        self + (other - self) * t
+    }
+
+    // unsigned angle between the two directions, 0 if either of them has no length
+    pub fn angle_to(self, other: Self) -> f64
+    {
+        let cos = self.dot(other) / (self.magnitude() * other.magnitude());
+
+        if cos.is_nan()
+        {
+            0.0
+        } else
+        {
+            cos.clamp(-1.0, 1.0).acos()
+        }
+    }
+
+    // how sharply a path coming from previous through this point towards next turns, 0 going straight on
+    pub fn turning_angle(self, previous: Self, next: Self) -> f64
+    {
+        (self - previous).angle_to(next - self)
+    }
 }
 
 impl Sub for Pos
@@ -99,10 +470,49 @@ impl Add for Pos
     }
 }
 
+impl Neg for Pos
+{
+    type Output = Self;
+
+    fn neg(self) -> Self::Output
+    {
+        Self{x: -self.x, y: -self.y}
+    }
+}
+
+impl Mul<f64> for Pos
+{
+    type Output = Self;
+
+    fn mul(self, scale: f64) -> Self::Output
+    {
+        Self{x: self.x * scale, y: self.y * scale}
+    }
+}
+
+// a traced border in order, closed borders end next to where they started
+struct Border
+{
+    points: Vec<Pos>,
+    closed: bool
+}
+
+impl Border
+{
+    pub fn into_curve(self) -> Curve
+    {
+        if self.closed
+        {
+            Curve::new_closed(self.points)
+        } else
+        {
+            Curve::new(self.points)
+        }
+    }
+}
+
 struct BinaryImage
 {
-    points: Vec<(i32, Pos)>,
-    last_index: i32,
     data: Vec<i32>,
     width: usize,
     height: usize
@@ -113,8 +523,6 @@ impl BinaryImage
     pub fn new(pixels: impl Iterator<Item=i32>, width: usize, height: usize) -> Self
     {
         Self{
-            points: Vec::new(),
-            last_index: 0,
             data: pixels.collect(),
             width,
             height
@@ -137,15 +545,14 @@ impl BinaryImage
         if let Some(index) = self.index_of(x, y)
         {
             self.data[index] = pixel;
+        }
+    }
 
-            let pos = Pos::new(x as f64 / self.width as f64, y as f64 / self.height as f64);
-            if self.last_index == pixel
-            {
-                self.points.push((pixel, pos));
-            }
+    pub fn pos(&self, x: i32, y: i32) -> Pos
+    {
+        let scale = self.width.max(self.height) as f64;
 
-            self.last_index = pixel;
-        }
+        Pos::new(x as f64 / scale, y as f64 / scale)
     }
 
     fn index_of(&self, x: i32, y: i32) -> Option<usize>
@@ -159,55 +566,149 @@ impl BinaryImage
         }
     }
 
-    pub fn curves(&self) -> Vec<Curve>
+    pub fn width(&self) -> usize
     {
-        let mut curves = Vec::new();
+        self.width
+    }
 
-        let mut points = self.points.iter().cloned().peekable();
-        while points.peek().is_some()
-        {
-            let mut curve = Vec::new();
-            while let Some((index, pos)) = points.next()
-            {
-                curve.push(pos);
-                if points.peek().map(|v| v.0 != index).unwrap_or(true)
-                {
-                    break;
-                }
-            }
+    pub fn height(&self) -> usize
+    {
+        self.height
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tracer
+{
+    Suzuki,
+    MarchingSquares,
+    Graph
+}
 
-            curves.push(Curve::new(curve));
+impl FromStr for Tracer
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        match s.to_lowercase().as_str()
+        {
+            "suzuki" => Ok(Self::Suzuki),
+            "marching" => Ok(Self::MarchingSquares),
+            "graph" => Ok(Self::Graph),
+            _ => Err(format!("unknown tracer: {s}"))
         }
+    }
+}
+
+// thin lines get traced on both sides, which ends up around a pixel apart
+fn retrace_tolerance(image: &FloatImage) -> f64
+{
+    image.pixel_size() * 1.5
+}
+
+pub fn trace(image: &FloatImage, threshold: f64, tracer: Tracer) -> Vec<Curve>
+{
+    match tracer
+    {
+        Tracer::Suzuki => contours(image, threshold),
+        Tracer::MarchingSquares =>
+        {
+            let tolerance = retrace_tolerance(image);
+
+            let curves = dedup::collapse_borders(marching::isolines(image, threshold), tolerance);
 
-        curves
+            dedup::drop_coincident(curves, tolerance)
+        },
+        Tracer::Graph => edge_graph::strokes(image, threshold)
     }
+}
 
-    pub fn width(&self) -> usize
+#[derive(Debug, Clone)]
+pub struct ContourNode
+{
+    pub hole: bool,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>
+}
+
+// every curve has a node at the same index describing where it is in the hierarchy
+pub struct ContourTree
+{
+    pub curves: Vec<Curve>,
+    pub nodes: Vec<ContourNode>
+}
+
+impl ContourTree
+{
+    // outer borders directly on the background are at depth 0
+    pub fn depth(&self, index: usize) -> usize
     {
-        self.width
+        let mut depth = 0;
+
+        let mut current = index;
+        while let Some(parent) = self.nodes[current].parent
+        {
+            depth += 1;
+            current = parent;
+        }
+
+        depth
     }
 
-    pub fn height(&self) -> usize
+    // outer shapes first and longest first in the same depth
+    pub fn outer_first(self, max_depth: Option<usize>) -> Vec<Curve>
     {
-        self.height
+        let depths = (0..self.nodes.len()).map(|index| self.depth(index)).collect::<Vec<_>>();
+
+        let mut curves = self.curves.into_iter().zip(depths)
+            .filter(|(curve, depth)|
+            {
+                curve.len() > 0 && max_depth.map(|max_depth| *depth <= max_depth).unwrap_or(true)
+            })
+            .collect::<Vec<_>>();
+
+        curves.sort_by(|(x, x_depth), (y, y_depth)|
+        {
+            x_depth.cmp(y_depth).then_with(||
+            {
+                y.curve_length().total_cmp(&x.curve_length())
+            })
+        });
+
+        curves.into_iter().map(|(curve, _)| curve).collect()
     }
 }
 
-pub fn contours(image: &FloatImage, threshold: f64, epsilon: f64) -> Vec<Curve>
+pub fn contours(image: &FloatImage, threshold: f64) -> Vec<Curve>
 {
+    contour_tree(image, threshold).curves.into_iter().filter(|curve|
+    {
+        curve.len() > 0
+    }).collect()
+}
+
+pub fn contour_tree(image: &FloatImage, threshold: f64) -> ContourTree
+{
+    let tolerance = retrace_tolerance(image);
+
     let mut image = BinaryImage::new(
         image.data.iter().map(|pixel|
         {
             (*pixel > threshold) as i32
         }),
-        image.width() as usize, image.height() as usize
+        image.width(), image.height()
     );
 
     //suzuki's contour tracing algorithm
+    // border 1 is the frame around the image, every other border nbd is at index nbd - 2
+    let mut nodes: Vec<ContourNode> = Vec::new();
+    let mut curves = Vec::new();
+
     let mut nbd = 1;
     for y in 0..image.height()
     {
-        let mut lnbd = 0;
+        let mut lnbd = 1;
 
         for x in 0..image.width()
         {
@@ -215,30 +716,72 @@ pub fn contours(image: &FloatImage, threshold: f64, epsilon: f64) -> Vec<Curve>
 
             let current_pixel = image.get(x, y);
 
-            if !(0..=1).contains(&current_pixel)
-            {
-                lnbd = current_pixel;
-            }
-
-            let is_outer = (x > 0 && image.get(x - 1, y) == 0) && current_pixel == 1;
+            let is_outer = current_pixel == 1 && image.get(x - 1, y) == 0;
+            let is_hole = !is_outer && current_pixel >= 1 && image.get(x + 1, y) == 0;
 
-            if is_outer && lnbd <= 0
+            if is_outer || is_hole
             {
                 nbd += 1;
-                contour_trace(&mut image, x, y, nbd);
 
-                if image.get(x, y) != 1
+                if is_hole && current_pixel > 1
+                {
+                    lnbd = current_pixel;
+                }
+
+                let (lnbd_index, lnbd_node) = if lnbd == 1
+                {
+                    (None, None)
+                } else
+                {
+                    let index = (lnbd - 2) as usize;
+
+                    (Some(index), Some(&nodes[index]))
+                };
+
+                let lnbd_hole = lnbd_node.map(|node| node.hole).unwrap_or(true);
+                let parent = if is_hole == lnbd_hole
+                {
+                    lnbd_node.and_then(|node| node.parent)
+                } else
+                {
+                    lnbd_index
+                };
+
+                let index = nodes.len();
+                if let Some(parent) = parent
                 {
-                    lnbd = image.get(x, y);
+                    nodes[parent].children.push(index);
                 }
-            } else if current_pixel != 1
+
+                nodes.push(ContourNode{hole: is_hole, parent, children: Vec::new()});
+
+                let from = if is_outer { (x - 1, y) } else { (x + 1, y) };
+                curves.push(border_follow(&mut image, (x, y), from, nbd).into_curve());
+            }
+
+            let current_pixel = image.get(x, y);
+            if current_pixel != 0 && current_pixel != 1
             {
-                lnbd = current_pixel;
+                lnbd = current_pixel.abs();
             }
         }
     }
 
-    simplify::simplify_borders(image.curves(), epsilon)
+    let mut curves = dedup::collapse_borders(curves, tolerance);
+
+    // a hole running right along its parent is the inside of a one pixel wide loop,
+    // it stays in the tree but its already drawn by the parent
+    for (index, node) in nodes.iter().enumerate()
+    {
+        let parent = node.parent.filter(|_| node.hole);
+
+        if parent.map(|parent| dedup::coincides(&curves[index], &curves[parent], tolerance)).unwrap_or(false)
+        {
+            curves[index] = Curve::new(Vec::new());
+        }
+    }
+
+    ContourTree{curves, nodes}
 }
 
 struct Neighbors
@@ -286,122 +829,263 @@ impl Neighbors
     }
 }
 
-fn contour_trace(image: &mut BinaryImage, x: i32, y: i32, nbd: i32)
+fn border_follow(image: &mut BinaryImage, start: (i32, i32), from: (i32, i32), nbd: i32) -> Border
 {
-    let mut start_pixel = (-1, 0);
-
     let neighbors = Neighbors::new();
 
-    let mut found_neighbor = (0, 0);
+    let offset = |pos: (i32, i32), center: (i32, i32)| (pos.0 - center.0, pos.1 - center.1);
 
-    for neighbor in neighbors.values
+    // clockwise around the starting pixel for the first nonzero neighbor
+    let from_index = Neighbors::lookup(offset(from, start));
+    let found = (from_index..(from_index + neighbors.len())).map(|index|
     {
-        if image.get(x + neighbor.0, y + neighbor.1) != 0
-        {
-            found_neighbor = neighbor;
-            break;
-        }
-    }
-
-    let mut follow_pixel = (0, 0);
-    let found_pixel = (0, 0);
+        neighbors.get(index)
+    }).find(|neighbor|
+    {
+        image.get(start.0 + neighbor.0, start.1 + neighbor.1) != 0
+    });
 
-    if found_neighbor != (0, 0)
+    let first = if let Some(found) = found
     {
-        start_pixel = found_neighbor;
-        follow_pixel = (0, 0);
+        (start.0 + found.0, start.1 + found.1)
     } else
     {
-        image.put(x, y, -nbd);
+        // a single pixel
+        image.put(start.0, start.1, -nbd);
+
+        return Border{points: vec![image.pos(start.0, start.1)], closed: false};
+    };
+
+    let mut points = Vec::new();
+
+    let mut previous = first;
+    let mut current = start;
+
+    loop
+    {
+        points.push(image.pos(current.0, current.1));
 
-        contour_trace4(image, follow_pixel, x, y, nbd);
+        let previous_index = Neighbors::lookup(offset(previous, current));
 
-        if !contour_trace5(
-            found_pixel,
-            &mut follow_pixel,
-            &mut start_pixel,
-            found_neighbor
-        )
+        // counter clockwise starting after the previous pixel, the previous pixel
+        // itself is checked last so something is always found
+        let mut right_zero = false;
+        let mut next = previous;
+        for index in (previous_index + 1..previous_index + neighbors.len()).rev()
         {
-            return;
+            let neighbor = neighbors.get(index);
+            let pos = (current.0 + neighbor.0, current.1 + neighbor.1);
+
+            if image.get(pos.0, pos.1) != 0
+            {
+                next = pos;
+                break;
+            }
+
+            if neighbor == (1, 0)
+            {
+                right_zero = true;
+            }
         }
-    }
 
-    while let Some(found) = contour_trace3(image, start_pixel, follow_pixel, x, y)
-    {
-        contour_trace4(image, follow_pixel, x, y, nbd);
+        if right_zero
+        {
+            image.put(current.0, current.1, -nbd);
+        } else if image.get(current.0, current.1) == 1
+        {
+            image.put(current.0, current.1, nbd);
+        }
 
-        if !contour_trace5(
-            found,
-            &mut follow_pixel,
-            &mut start_pixel,
-            found_neighbor
-        )
+        if next == start && current == first
         {
             break;
         }
+
+        previous = current;
+        current = next;
     }
+
+    Border{points, closed: true}
 }
 
-fn contour_trace3(
-    image: &BinaryImage,
-    start_pixel: (i32, i32),
-    follow_pixel: (i32, i32),
-    x: i32,
-    y: i32
-) -> Option<(i32, i32)>
+#[cfg(test)]
+mod tests
 {
-    let direction = (start_pixel.0 - follow_pixel.0, start_pixel.1 - follow_pixel.1);
-    let start_index = Neighbors::lookup(direction);
+    use super::*;
 
-    let neighbors = Neighbors::new();
+    // a one pixel wide square outline starting at offset
+    fn ring_image(size: usize, ring: usize, offset: usize) -> FloatImage
+    {
+        let data = (0..size * size).map(|index|
+        {
+            let (x, y) = (index % size, index / size);
+            let (x, y) = (x.wrapping_sub(offset), y.wrapping_sub(offset));
+
+            let on_ring = x < ring && y < ring && (x == 0 || y == 0 || x == ring - 1 || y == ring - 1);
 
-    // iterating over all elements around follow_pixel starting from start_pixel
-    // in counter clockwise order
-    for neighbor_index in (start_index..(start_index + neighbors.len()))
-        .rev().skip(1)
+            if on_ring { 1.0 } else { 0.0 }
+        }).collect();
+
+        FloatImage::new(data, size, size)
+    }
+
+    fn corner() -> Curve
+    {
+        Curve::new(vec![Pos::new(0.0, 0.0), Pos::new(1.0, 0.0), Pos::new(1.0, 0.5)])
+    }
+
+    #[test]
+    fn resample_spaces_points_evenly()
     {
-        let neighbor = neighbors.get(neighbor_index);
+        let resampled = corner().resample(0.1);
 
-        let pos = (follow_pixel.0 + neighbor.0, follow_pixel.1 + neighbor.1);
-        if image.get(x + pos.0, y + pos.1) != 0
+        assert_eq!(resampled.len(), 16);
+        assert_eq!(resampled.start(), Pos::new(0.0, 0.0));
+        assert_eq!(resampled.end(), Pos::new(1.0, 0.5));
+
+        for (a, b) in resampled.segments()
         {
-            return Some(pos);
+            assert!((b - a).magnitude() <= 0.1 + 1e-9);
         }
     }
 
-    None
-}
+    #[test]
+    fn subdivide_keeps_every_point()
+    {
+        let subdivided = corner().subdivide(0.3);
 
-fn contour_trace4(image: &mut BinaryImage, follow_pixel: (i32, i32), x: i32, y: i32, nbd: i32)
-{
-    let (x, y) = (x + follow_pixel.0, y + follow_pixel.1);
+        assert_eq!(subdivided.len(), 7);
+        assert!(corner().points().iter().all(|point| subdivided.points().contains(point)));
+        assert!(subdivided.segments().all(|(a, b)| (b - a).magnitude() <= 0.3));
+    }
 
-    let right_pixel = image.get(x + 1, y);
-    if right_pixel == 0
+    fn close(a: Pos, b: Pos) -> bool
     {
-        image.put(x, y, -nbd);
-    } else if image.get(x, y) == 1
+        (a - b).magnitude() < 1e-9
+    }
+
+    #[test]
+    fn signed_area_follows_the_orientation()
     {
-        image.put(x, y, nbd);
+        // right, down, left on screen is clockwise
+        let mut square = Curve::new_closed(vec![
+            Pos::new(0.0, 0.0),
+            Pos::new(2.0, 0.0),
+            Pos::new(2.0, 2.0),
+            Pos::new(0.0, 2.0)
+        ]);
+        assert_eq!(square.signed_area(), 4.0);
+
+        square.reverse();
+        assert_eq!(square.signed_area(), -4.0);
     }
-}
 
-fn contour_trace5(
-    found_pixel: (i32, i32),
-    follow_pixel: &mut (i32, i32),
-    start_pixel: &mut (i32, i32),
-    found_neighbor: (i32, i32)
-) -> bool
-{
-    if found_pixel == (0, 0) && *follow_pixel == found_neighbor
+    #[test]
+    fn centroid_is_the_center_of_the_area()
     {
-        false
-    } else
+        let triangle = Curve::new_closed(vec![Pos::new(0.0, 0.0), Pos::new(3.0, 0.0), Pos::new(0.0, 3.0)]);
+        assert!(close(triangle.centroid(), Pos::new(1.0, 1.0)));
+
+        // more points along one side dont pull the center over
+        let mut points = test_square(true).into_iter().collect::<Vec<_>>();
+        points.splice(1..1, (1..50).map(|index| Pos::new(index as f64 / 500.0, 0.0)));
+        assert!(close(Curve::new_closed(points).centroid(), Pos::new(0.5, 0.5)));
+
+        assert!(close(corner().centroid(), Pos::new(0.5 * 2.0 / 3.0 + 1.0 / 3.0, 0.25 / 3.0)));
+    }
+
+    #[test]
+    fn split_at_index()
+    {
+        let (first, second) = corner().split_at(1);
+        assert_eq!(first.points(), &corner().points()[..=1]);
+        assert_eq!(second.unwrap().points(), &corner().points()[1..]);
+
+        let (opened, second) = test_square(true).split_at(10);
+        assert!(second.is_none());
+        assert!(!opened.is_closed());
+        assert_eq!(opened.len(), 41);
+        assert_eq!(opened.start(), Pos::new(1.0, 0.0));
+        assert_eq!(opened.end(), Pos::new(1.0, 0.0));
+    }
+
+    #[test]
+    fn split_at_parameter_walks_the_arc_length()
+    {
+        // 1.5 long, 0.8 of it is 1.2 along which is 0.2 past the corner
+        let (first, second) = corner().split_at_parameter(0.8);
+
+        assert!(close(first.end(), Pos::new(1.0, 0.2)));
+        assert!(close(second.start(), Pos::new(1.0, 0.2)));
+        assert!((first.curve_length() - 1.2).abs() < 1e-9);
+        assert!((second.curve_length() - 0.3).abs() < 1e-9);
+
+        // right on the corner it isnt repeated
+        let (first, second) = corner().split_at_parameter(2.0 / 3.0);
+        assert_eq!(first.points(), &corner().points()[..=1]);
+        assert_eq!(second.points(), &corner().points()[1..]);
+    }
+
+    #[test]
+    fn nearest_point_projects_onto_segments()
+    {
+        assert_eq!(corner().nearest_point(Pos::new(0.5, -1.0)), (0, Pos::new(0.5, 0.0)));
+        assert_eq!(corner().nearest_point(Pos::new(1.5, 0.25)), (1, Pos::new(1.0, 0.25)));
+
+        // past the end it stays on the last point
+        assert_eq!(corner().nearest_point(Pos::new(1.0, 3.0)), (1, Pos::new(1.0, 0.5)));
+
+        // closed curves also look at the line back to the start
+        assert_eq!(test_square(true).nearest_point(Pos::new(-1.0, 0.05)), (39, Pos::new(0.0, 0.05)));
+    }
+
+    #[test]
+    fn turning_angle_is_unsigned()
+    {
+        let point = Pos::new(1.0, 0.0);
+
+        assert_eq!(point.turning_angle(Pos::new(0.0, 0.0), Pos::new(2.0, 0.0)), 0.0);
+        assert!((point.turning_angle(Pos::new(0.0, 0.0), Pos::new(1.0, 1.0)) - std::f64::consts::FRAC_PI_2).abs() < 1e-9);
+        assert!((point.turning_angle(Pos::new(0.0, 0.0), Pos::new(1.0, -1.0)) - std::f64::consts::FRAC_PI_2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn thin_ring_is_traced_once()
+    {
+        let image = ring_image(20, 16, 2);
+
+        let tree = contour_tree(&image, 0.5);
+        assert_eq!(tree.nodes.len(), 2);
+        assert!(tree.nodes[1].hole);
+        assert_eq!(tree.nodes[1].parent, Some(0));
+
+        let curves = tree.outer_first(None);
+        assert_eq!(curves.len(), 1);
+        assert_eq!(curves[0].len(), 60);
+    }
+
+    #[test]
+    fn wide_ring_keeps_its_hole()
+    {
+        let data = (0..20 * 20).map(|index|
+        {
+            let (x, y) = (index % 20, index / 20);
+            let inside = |low, high| (low..high).contains(&x) && (low..high).contains(&y);
+
+            if inside(2, 18) && !inside(6, 14) { 1.0 } else { 0.0 }
+        }).collect();
+
+        let curves = contours(&FloatImage::new(data, 20, 20), 0.5);
+        assert_eq!(curves.len(), 2);
+    }
+
+    #[test]
+    fn thin_ring_isolines_are_traced_once()
     {
-        *start_pixel = *follow_pixel;
-        *follow_pixel = found_pixel;
+        let image = ring_image(20, 16, 2);
 
-        true
+        let curves = trace(&image, 0.5, Tracer::MarchingSquares);
+        assert_eq!(curves.len(), 1);
+        assert!(curves[0].is_closed());
     }
 }
@@ -0,0 +1,139 @@
+use std::fmt::Write;
+
+use super::{Curve, Pos};
+
+
+#[derive(Debug, Clone, Copy)]
+pub enum Segment
+{
+    Line(Pos),
+    // two control points and the end
+    Cubic(Pos, Pos, Pos)
+}
+
+// a chain of lines and cubic beziers, each one starting where the last one ended
+#[derive(Debug, Clone)]
+pub struct BezierPath
+{
+    pub start: Pos,
+    pub segments: Vec<Segment>,
+    pub closed: bool
+}
+
+fn cubic_point(p0: Pos, p1: Pos, p2: Pos, p3: Pos, t: f64) -> Pos
+{
+    let u = 1.0 - t;
+
+    p0 * (u * u * u) + p1 * (3.0 * u * u * t) + p2 * (3.0 * u * t * t) + p3 * (t * t * t)
+}
+
+impl BezierPath
+{
+    pub fn from_curve(curve: &Curve) -> Self
+    {
+        let (start, rest) = curve.points().split_first().unwrap();
+
+        Self{start: *start, segments: rest.iter().copied().map(Segment::Line).collect(), closed: curve.is_closed()}
+    }
+
+    // lines as they are, cubics split evenly into enough pieces to stay within tolerance
+    pub fn flatten(&self, tolerance: f64) -> Curve
+    {
+        let mut points = vec![self.start];
+
+        for segment in &self.segments
+        {
+            let current = *points.last().unwrap();
+
+            match *segment
+            {
+                Segment::Line(end) => points.push(end),
+                Segment::Cubic(c1, c2, end) =>
+                {
+                    // uniform steps are off by at most 3/4 of the biggest second difference over steps squared
+                    let bend = (current - c1 * 2.0 + c2).magnitude().max((c1 - c2 * 2.0 + end).magnitude());
+                    let steps = (0.75 * bend / tolerance).sqrt().ceil().max(1.0) as usize;
+
+                    points.extend((1..=steps).map(|step|
+                    {
+                        cubic_point(current, c1, c2, end, step as f64 / steps as f64)
+                    }));
+                }
+            }
+        }
+
+        // closed paths that come back to the start dont need the point twice
+        if self.closed && points.len() > 1 && points.last() == Some(&self.start)
+        {
+            points.pop();
+        }
+
+        Curve::from_points(points, self.closed)
+    }
+
+    // svg path data with every coordinate multiplied by scale
+    pub fn svg_data(&self, scale: f64) -> String
+    {
+        let point = |pos: Pos| format!("{:.2} {:.2}", pos.x * scale, pos.y * scale);
+
+        let mut data = format!("M {}", point(self.start));
+        for segment in &self.segments
+        {
+            let _ = match *segment
+            {
+                Segment::Line(end) => write!(data, " L {}", point(end)),
+                Segment::Cubic(c1, c2, end) =>
+                {
+                    write!(data, " C {} {} {}", point(c1), point(c2), point(end))
+                }
+            };
+        }
+
+        if self.closed
+        {
+            data.push_str(" Z");
+        }
+
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn closed_curves_flatten_back_to_themselves()
+    {
+        let square = Curve::new_closed(vec![
+            Pos::new(0.0, 0.0),
+            Pos::new(1.0, 0.0),
+            Pos::new(1.0, 1.0),
+            Pos::new(0.0, 1.0)
+        ]);
+
+        let flattened = BezierPath::from_curve(&square).flatten(0.01);
+
+        assert!(flattened.is_closed());
+        assert_eq!(flattened.points(), square.points());
+    }
+
+    #[test]
+    fn closing_segment_is_not_repeated()
+    {
+        let path = BezierPath{
+            start: Pos::new(0.0, 0.0),
+            segments: vec![
+                Segment::Cubic(Pos::new(0.5, -0.5), Pos::new(1.5, -0.5), Pos::new(2.0, 0.0)),
+                Segment::Cubic(Pos::new(1.5, 0.5), Pos::new(0.5, 0.5), Pos::new(0.0, 0.0))
+            ],
+            closed: true
+        };
+
+        let flattened = path.flatten(0.001);
+
+        assert_ne!(flattened.points().last(), Some(&path.start));
+        assert!(flattened.len() > 4);
+    }
+}
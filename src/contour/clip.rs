@@ -0,0 +1,113 @@
+use super::{Curve, Pos};
+
+
+// liang barsky, the part of the line from a to b inside the box as fractions along it
+fn clip_line(a: Pos, b: Pos, low: Pos, high: Pos) -> Option<(f64, f64)>
+{
+    let direction = b - a;
+
+    let (mut enter, mut exit) = (0.0_f64, 1.0_f64);
+
+    let edges = [
+        (-direction.x, a.x - low.x),
+        (direction.x, high.x - a.x),
+        (-direction.y, a.y - low.y),
+        (direction.y, high.y - a.y)
+    ];
+
+    for (p, q) in edges
+    {
+        if p == 0.0
+        {
+            if q < 0.0
+            {
+                return None;
+            }
+        } else
+        {
+            let t = q / p;
+
+            if p < 0.0
+            {
+                enter = enter.max(t);
+            } else
+            {
+                exit = exit.min(t);
+            }
+        }
+    }
+
+    (enter <= exit).then_some((enter, exit))
+}
+
+fn inside(pos: Pos, low: Pos, high: Pos) -> bool
+{
+    (low.x..=high.x).contains(&pos.x) && (low.y..=high.y).contains(&pos.y)
+}
+
+// cuts away everything outside the box, a curve leaving and coming back splits in two
+pub fn clip_curve(curve: Curve, low: Pos, high: Pos) -> Vec<Curve>
+{
+    if curve.points().iter().all(|pos| inside(*pos, low, high))
+    {
+        return vec![curve];
+    }
+
+    let closed = curve.is_closed();
+
+    let mut points = curve.into_iter().collect::<Vec<_>>();
+    if points.len() < 2
+    {
+        return Vec::new();
+    }
+
+    if closed
+    {
+        points.push(points[0]);
+    }
+
+    let mut pieces: Vec<Vec<Pos>> = Vec::new();
+    let mut current: Vec<Pos> = Vec::new();
+
+    for pair in points.windows(2)
+    {
+        let (a, b) = (pair[0], pair[1]);
+
+        if let Some((enter, exit)) = clip_line(a, b, low, high)
+        {
+            if enter > 0.0 && !current.is_empty()
+            {
+                pieces.push(current.split_off(0));
+            }
+
+            if current.is_empty()
+            {
+                current.push(a.lerp(b, enter));
+            }
+
+            current.push(a.lerp(b, exit));
+
+            if exit < 1.0
+            {
+                pieces.push(current.split_off(0));
+            }
+        } else if !current.is_empty()
+        {
+            pieces.push(current.split_off(0));
+        }
+    }
+
+    if !current.is_empty()
+    {
+        pieces.push(current);
+    }
+
+    // a loop cut open somewhere other than its start continues from its last piece into its first
+    if closed && pieces.len() > 1 && inside(points[0], low, high)
+    {
+        let first = pieces.remove(0);
+        pieces.last_mut().unwrap().extend(first.into_iter().skip(1));
+    }
+
+    pieces.into_iter().filter(|piece| piece.len() > 1).map(Curve::new).collect()
+}
@@ -0,0 +1,199 @@
+use super::Curve;
+
+
+// a curve cut at its corners, the pieces share their corner points
+pub struct CornerPieces
+{
+    pieces: Vec<Curve>,
+    closed: bool
+}
+
+impl CornerPieces
+{
+    pub fn map(self, f: impl FnOnce(Vec<Curve>) -> Vec<Curve>) -> Self
+    {
+        Self{pieces: f(self.pieces), closed: self.closed}
+    }
+
+    pub fn into_strokes(self) -> Vec<Curve>
+    {
+        self.pieces
+    }
+
+    pub fn into_curve(self) -> Curve
+    {
+        let closed = self.closed;
+        let mut pieces = self.pieces.into_iter();
+
+        let first = pieces.next().unwrap();
+        if first.is_closed()
+        {
+            return first;
+        }
+
+        let mut points = first.into_iter().collect::<Vec<_>>();
+        pieces.for_each(|piece| points.extend(piece.into_iter().skip(1)));
+
+        if closed
+        {
+            points.pop();
+        }
+
+        Curve::from_points(points, closed)
+    }
+}
+
+// walks along the curve until a point at least support away, none if it runs out
+fn reach(curve: &Curve, index: usize, forward: bool, support: f64) -> Option<usize>
+{
+    let len = curve.len();
+
+    let mut current = index;
+    for _ in 1..len
+    {
+        current = match (forward, curve.is_closed())
+        {
+            (true, true) => (current + 1) % len,
+            (false, true) => (current + len - 1) % len,
+            (true, false) => (current + 1 < len).then_some(current + 1)?,
+            (false, false) => current.checked_sub(1)?
+        };
+
+        if (curve[current] - curve[index]).magnitude() >= support
+        {
+            return Some(current);
+        }
+    }
+
+    None
+}
+
+// points where the curve turns sharper than max_angle measured over support distance
+// on both sides, so pixel stairs dont count but real corners do
+pub fn detect_corners(curve: &Curve, support: f64, max_angle: f64) -> Vec<usize>
+{
+    let len = curve.len();
+
+    let regions = (0..len).map(|index|
+    {
+        let before = reach(curve, index, false, support)?;
+        let after = reach(curve, index, true, support)?;
+
+        let angle = curve[index].turning_angle(curve[before], curve[after]);
+
+        Some((before, after, angle))
+    }).collect::<Vec<_>>();
+
+    let angle_at = |index: usize| regions[index].map(|(_, _, angle)| angle).unwrap_or(0.0);
+
+    (0..len).filter(|&index|
+    {
+        let (before, after, angle) = if let Some(region) = regions[index]
+        {
+            region
+        } else
+        {
+            return false;
+        };
+
+        if angle <= max_angle
+        {
+            return false;
+        }
+
+        // only the sharpest point around a corner counts, ties go to the first one
+        let steps_before = (index + len - before) % len;
+        let steps_after = (after + len - index) % len;
+
+        let sharper_before = (1..=steps_before).any(|step| angle_at((index + len - step) % len) >= angle);
+        let sharper_after = (1..=steps_after).any(|step| angle_at((index + step) % len) > angle);
+
+        !sharper_before && !sharper_after
+    }).collect()
+}
+
+pub fn split_at_corners(curve: Curve, support: f64, max_angle: f64) -> CornerPieces
+{
+    let closed = curve.is_closed();
+
+    let corners = if curve.len() < 3
+    {
+        Vec::new()
+    } else
+    {
+        detect_corners(&curve, support, max_angle)
+    };
+
+    if corners.is_empty()
+    {
+        return CornerPieces{pieces: vec![curve], closed};
+    }
+
+    let mut corners = corners.into_iter();
+
+    let mut rest = curve;
+    let mut offset = 0;
+    if closed
+    {
+        // closed curves start and end at their first corner
+        offset = corners.next().unwrap();
+        rest = rest.split_at(offset).0;
+    }
+
+    let mut pieces = Vec::new();
+    for corner in corners
+    {
+        let (piece, remaining) = rest.split_at(corner - offset);
+
+        pieces.push(piece);
+        rest = remaining.unwrap();
+        offset = corner;
+    }
+
+    pieces.push(rest);
+
+    CornerPieces{pieces, closed}
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::contour::{Pos, test_square};
+
+    #[test]
+    fn square_has_four_corners()
+    {
+        let corners = detect_corners(&test_square(true), 0.25, 1.0);
+
+        assert_eq!(corners, vec![0, 10, 20, 30]);
+    }
+
+    #[test]
+    fn pieces_share_their_corners()
+    {
+        let pieces = split_at_corners(test_square(true), 0.25, 1.0).into_strokes();
+
+        assert_eq!(pieces.len(), 4);
+        for pair in pieces.windows(2)
+        {
+            assert_eq!(pair[0].end(), pair[1].start());
+        }
+
+        assert_eq!(pieces[3].end(), pieces[0].start());
+
+        let open = split_at_corners(test_square(false), 0.25, 1.0).into_strokes();
+        assert_eq!(open.len(), 4);
+        assert_eq!(open[0].start(), Pos::new(0.0, 0.0));
+        assert_eq!(open[3].end(), test_square(false).end());
+    }
+
+    #[test]
+    fn pieces_join_back_into_the_curve()
+    {
+        let curve = split_at_corners(test_square(true), 0.25, 1.0).into_curve();
+
+        assert!(curve.is_closed());
+        assert_eq!(curve.len(), 40);
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use super::{Curve, Pos, simplify::farthest_from};
+
+
+struct SparseGrid
+{
+    cells: HashMap<(i64, i64), Vec<Pos>>,
+    cell_size: f64
+}
+
+impl SparseGrid
+{
+    pub fn new(points: impl Iterator<Item=Pos>, cell_size: f64) -> Self
+    {
+        let mut grid = Self{cells: HashMap::new(), cell_size};
+
+        for point in points
+        {
+            grid.cells.entry(grid.cell_of(point)).or_default().push(point);
+        }
+
+        grid
+    }
+
+    fn cell_of(&self, pos: Pos) -> (i64, i64)
+    {
+        ((pos.x / self.cell_size).floor() as i64, (pos.y / self.cell_size).floor() as i64)
+    }
+
+    pub fn any_within(&self, pos: Pos, radius: f64) -> bool
+    {
+        let (x, y) = self.cell_of(pos);
+
+        (y - 1..=y + 1).flat_map(|y| (x - 1..=x + 1).map(move |x| (x, y))).any(|cell|
+        {
+            self.cells.get(&cell).map(|points|
+            {
+                points.iter().any(|other| (*other - pos).magnitude() <= radius)
+            }).unwrap_or(false)
+        })
+    }
+}
+
+fn covers(curve: &[Pos], other: &[Pos], tolerance: f64) -> bool
+{
+    let grid = SparseGrid::new(curve.iter().copied(), tolerance);
+
+    other.iter().all(|pos| grid.any_within(*pos, tolerance))
+}
+
+// a loop that goes out along a thin line and comes back along the same line
+// becomes an open curve going through it once
+pub fn collapse_retraced(curve: Curve, tolerance: f64) -> Curve
+{
+    if !curve.is_closed() || curve.len() < 2
+    {
+        return curve;
+    }
+
+    let start = farthest_from(&curve, 0);
+    let end = farthest_from(&curve, start);
+
+    let points = curve.into_iter().collect::<Vec<_>>();
+
+    let (start, end) = (start.min(end), start.max(end));
+
+    let there = &points[start..=end];
+    let back = points[end..].iter().chain(points[..=start].iter()).copied().collect::<Vec<_>>();
+
+    if covers(there, &back, tolerance) && covers(&back, there, tolerance)
+    {
+        Curve::new(there.to_vec())
+    } else
+    {
+        Curve::new_closed(points)
+    }
+}
+
+// two closed borders running along each other, like both sides of a one pixel wide loop
+pub fn coincides(curve: &Curve, other: &Curve, tolerance: f64) -> bool
+{
+    if curve.len() == 0 || other.len() == 0
+    {
+        return false;
+    }
+
+    let close = |a: Pos, b: Pos| (a.x - b.x).abs() <= tolerance && (a.y - b.y).abs() <= tolerance;
+
+    let ((low, high), (other_low, other_high)) = (curve.bounding_box(), other.bounding_box());
+    if !close(low, other_low) || !close(high, other_high)
+    {
+        return false;
+    }
+
+    covers(curve.points(), other.points(), tolerance) && covers(other.points(), curve.points(), tolerance)
+}
+
+pub fn collapse_borders(curves: Vec<Curve>, tolerance: f64) -> Vec<Curve>
+{
+    curves.into_iter().map(|curve| collapse_retraced(curve, tolerance)).collect()
+}
+
+// a thin closed line gets traced as a loop on each side of it, only the first loop is kept
+pub fn drop_coincident(curves: Vec<Curve>, tolerance: f64) -> Vec<Curve>
+{
+    let mut kept: Vec<Curve> = Vec::new();
+
+    for curve in curves
+    {
+        let duplicate = curve.is_closed() && kept.iter().any(|other|
+        {
+            other.is_closed() && coincides(&curve, other, tolerance)
+        });
+
+        if !duplicate
+        {
+            kept.push(curve);
+        }
+    }
+
+    kept
+}
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use super::{Curve, Pos};
+
+
+struct EndpointGrid
+{
+    cells: HashMap<(i64, i64), Vec<(usize, bool)>>,
+    cell_size: f64
+}
+
+impl EndpointGrid
+{
+    pub fn new(curves: &[Option<Curve>], cell_size: f64) -> Self
+    {
+        let mut grid = Self{cells: HashMap::new(), cell_size};
+
+        for (index, curve) in curves.iter().enumerate()
+        {
+            if let Some(curve) = curve
+            {
+                for (pos, is_start) in [(curve.start(), true), (curve.end(), false)]
+                {
+                    grid.cells.entry(grid.cell_of(pos)).or_default().push((index, is_start));
+                }
+            }
+        }
+
+        grid
+    }
+
+    fn cell_of(&self, pos: Pos) -> (i64, i64)
+    {
+        ((pos.x / self.cell_size).floor() as i64, (pos.y / self.cell_size).floor() as i64)
+    }
+
+    // every curve endpoint in the cells around the position
+    pub fn around(&self, pos: Pos) -> impl Iterator<Item=(usize, bool)> + '_
+    {
+        let (x, y) = self.cell_of(pos);
+
+        (y - 1..=y + 1).flat_map(move |y| (x - 1..=x + 1).map(move |x| (x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+    }
+}
+
+// turn at the joint with the next curve moved so it starts right where this one ends
+fn turning_angle(curve: &Curve, next: &Curve) -> f64
+{
+    let end = curve.end();
+
+    end.turning_angle(curve[curve.len() - 2], next[1] + (end - next.start()))
+}
+
+// keeps appending the closest curve that starts (or ends) near the end of this one
+fn extend(
+    curve: &mut Curve,
+    index: usize,
+    curves: &mut [Option<Curve>],
+    grid: &EndpointGrid,
+    tolerance: f64,
+    max_angle: Option<f64>
+)
+{
+    loop
+    {
+        let end = curve.end();
+
+        let closest = grid.around(end).filter_map(|(other, is_start)|
+        {
+            if other == index
+            {
+                return None;
+            }
+
+            let other_curve = curves[other].as_ref()?;
+
+            let pos = if is_start { other_curve.start() } else { other_curve.end() };
+            let distance = (pos - end).magnitude();
+
+            (distance <= tolerance).then_some((other, is_start, distance))
+        }).filter(|&(other, is_start, _)|
+        {
+            max_angle.map(|max_angle|
+            {
+                let mut other = curves[other].clone().unwrap();
+                if !is_start
+                {
+                    other.reverse();
+                }
+
+                turning_angle(curve, &other) <= max_angle
+            }).unwrap_or(true)
+        }).min_by(|a, b| a.2.total_cmp(&b.2));
+
+        if let Some((other, is_start, _)) = closest
+        {
+            let mut other = curves[other].take().unwrap();
+            if !is_start
+            {
+                other.reverse();
+            }
+
+            curve.append(&mut other);
+        } else
+        {
+            return;
+        }
+    }
+}
+
+// joins open curves whose ends are close together into longer ones
+// max_angle (in radians) stops sharp turns from merging separate shapes
+pub fn join_curves(curves: Vec<Curve>, tolerance: f64, max_angle: Option<f64>) -> Vec<Curve>
+{
+    if tolerance <= 0.0
+    {
+        return curves;
+    }
+
+    let (joinable, mut joined): (Vec<_>, Vec<_>) = curves.into_iter().partition(|curve|
+    {
+        !curve.is_closed() && curve.len() > 1
+    });
+
+    let mut curves = joinable.into_iter().map(Some).collect::<Vec<_>>();
+    let grid = EndpointGrid::new(&curves, tolerance);
+
+    for index in 0..curves.len()
+    {
+        let mut curve = if let Some(curve) = curves[index].take()
+        {
+            curve
+        } else
+        {
+            continue;
+        };
+
+        extend(&mut curve, index, &mut curves, &grid, tolerance, max_angle);
+
+        curve.reverse();
+        extend(&mut curve, index, &mut curves, &grid, tolerance, max_angle);
+        curve.reverse();
+
+        let loops_back = curve.len() > 2 && (curve.end() - curve.start()).magnitude() <= tolerance;
+
+        joined.push(if loops_back
+        {
+            Curve::new_closed(curve.into_iter().collect())
+        } else
+        {
+            curve
+        });
+    }
+
+    joined
+}
@@ -0,0 +1,198 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{Curve, Pos, FloatImage};
+
+
+struct Crossing
+{
+    pos: Pos,
+    neighbors: Vec<usize>
+}
+
+struct IsolineGraph<'a>
+{
+    image: &'a FloatImage,
+    threshold: f64,
+    crossings: HashMap<usize, Crossing>
+}
+
+impl<'a> IsolineGraph<'a>
+{
+    pub fn new(image: &'a FloatImage, threshold: f64) -> Self
+    {
+        Self{image, threshold, crossings: HashMap::new()}
+    }
+
+    fn value(&self, x: usize, y: usize) -> f64
+    {
+        self.image.get(x, y).unwrap()
+    }
+
+    fn horizontal_edge(&self, x: usize, y: usize) -> usize
+    {
+        (y * self.image.width() + x) * 2
+    }
+
+    fn vertical_edge(&self, x: usize, y: usize) -> usize
+    {
+        (y * self.image.width() + x) * 2 + 1
+    }
+
+    // position where the threshold is crossed between two neighboring pixels
+    fn crossing(&self, edge: usize) -> Pos
+    {
+        let index = edge / 2;
+        let (x, y) = (index % self.image.width(), index / self.image.width());
+
+        let (other_x, other_y) = if edge.is_multiple_of(2) { (x + 1, y) } else { (x, y + 1) };
+
+        let (value, other_value) = (self.value(x, y), self.value(other_x, other_y));
+        let a = ((self.threshold - value) / (other_value - value)).clamp(0.0, 1.0);
+
+        let x = x as f64 + (other_x - x) as f64 * a;
+        let y = y as f64 + (other_y - y) as f64 * a;
+
+        self.image.normalized(x, y)
+    }
+
+    fn connect(&mut self, a: usize, b: usize)
+    {
+        for (edge, other) in [(a, b), (b, a)]
+        {
+            if !self.crossings.contains_key(&edge)
+            {
+                let pos = self.crossing(edge);
+                self.crossings.insert(edge, Crossing{pos, neighbors: Vec::new()});
+            }
+
+            self.crossings.get_mut(&edge).unwrap().neighbors.push(other);
+        }
+    }
+
+    fn add_cell(&mut self, x: usize, y: usize)
+    {
+        let inside = |x, y| self.value(x, y) > self.threshold;
+
+        let case = (inside(x, y) as u8) << 3
+            | (inside(x + 1, y) as u8) << 2
+            | (inside(x + 1, y + 1) as u8) << 1
+            | inside(x, y + 1) as u8;
+
+        let top = self.horizontal_edge(x, y);
+        let right = self.vertical_edge(x + 1, y);
+        let bottom = self.horizontal_edge(x, y + 1);
+        let left = self.vertical_edge(x, y);
+
+        let center_inside = || -> bool
+        {
+            let sum = self.value(x, y) + self.value(x + 1, y)
+                + self.value(x + 1, y + 1) + self.value(x, y + 1);
+
+            sum / 4.0 > self.threshold
+        };
+
+        let segments: &[(usize, usize)] = match case
+        {
+            1 | 14 => &[(left, bottom)],
+            2 | 13 => &[(bottom, right)],
+            3 | 12 => &[(left, right)],
+            4 | 11 => &[(top, right)],
+            6 | 9 => &[(top, bottom)],
+            7 | 8 => &[(left, top)],
+            // saddles, the average of the corners decides which ones are connected
+            5 => if center_inside()
+            {
+                &[(left, top), (bottom, right)]
+            } else
+            {
+                &[(top, right), (left, bottom)]
+            },
+            10 => if center_inside()
+            {
+                &[(top, right), (left, bottom)]
+            } else
+            {
+                &[(left, top), (bottom, right)]
+            },
+            _ => &[]
+        };
+
+        for &(a, b) in segments
+        {
+            self.connect(a, b);
+        }
+    }
+
+    fn follow(&self, start: usize, visited: &mut HashSet<usize>) -> Curve
+    {
+        let mut points = vec![self.crossings[&start].pos];
+        visited.insert(start);
+
+        let mut current = start;
+        loop
+        {
+            let next = self.crossings[&current].neighbors.iter().find(|edge|
+            {
+                !visited.contains(edge)
+            });
+
+            if let Some(&next) = next
+            {
+                visited.insert(next);
+                points.push(self.crossings[&next].pos);
+
+                current = next;
+            } else
+            {
+                // closed loops end next to where they started
+                let start_neighbors = &self.crossings[&start].neighbors;
+                if start_neighbors.len() == 2 && start_neighbors.contains(&current)
+                {
+                    return Curve::new_closed(points);
+                }
+
+                break;
+            }
+        }
+
+        Curve::new(points)
+    }
+
+    pub fn isolines(&self) -> Vec<Curve>
+    {
+        let mut visited = HashSet::new();
+
+        let mut edges = self.crossings.keys().copied().collect::<Vec<_>>();
+        edges.sort_unstable();
+
+        // open lines have to start from one of their ends
+        let ends = edges.iter().filter(|edge| self.crossings[edge].neighbors.len() == 1);
+
+        let mut curves = Vec::new();
+        for edge in ends.chain(edges.iter())
+        {
+            if !visited.contains(edge)
+            {
+                curves.push(self.follow(*edge, &mut visited));
+            }
+        }
+
+        curves
+    }
+}
+
+// marching squares over the continuous image, with crossings interpolated between pixels
+pub fn isolines(image: &FloatImage, threshold: f64) -> Vec<Curve>
+{
+    let mut graph = IsolineGraph::new(image, threshold);
+
+    for y in 0..image.height().saturating_sub(1)
+    {
+        for x in 0..image.width().saturating_sub(1)
+        {
+            graph.add_cell(x, y);
+        }
+    }
+
+    graph.isolines()
+}
@@ -0,0 +1,353 @@
+use super::{
+    Curve,
+    Pos,
+    bezier::{BezierPath, Segment}
+};
+
+
+// how far in pixels a polygon side can pass from the points it replaces
+const STRAIGHT_TOLERANCE: f64 = 0.5;
+
+// sides shorter than this in pixels get merged into a corner
+const SHORT_SIDE: f64 = 2.0;
+
+// potraces alphamax, vertices bent more than this become corners
+const ALPHA_MAX: f64 = 1.0;
+
+// for every point the furthest point a straight side starting there can reach,
+// every point in between has to be within tolerance of the side
+fn reaches(points: &[Pos], tolerance: f64, max_span: usize) -> Vec<usize>
+{
+    (0..points.len()).map(|start|
+    {
+        let origin = points[start];
+
+        // allowed directions as angles relative to the first point thats far enough away
+        let mut reference: Option<Pos> = None;
+        let (mut low, mut high) = (-f64::MAX, f64::MAX);
+
+        let mut reach = start;
+        for (index, point) in points.iter().enumerate().take(start + max_span + 1).skip(start + 1)
+        {
+            let offset = *point - origin;
+            let distance = offset.magnitude();
+
+            if distance > tolerance
+            {
+                let reference = *reference.get_or_insert(offset);
+                let angle = reference.cross(offset).atan2(reference.dot(offset));
+
+                if angle < low || angle > high
+                {
+                    break;
+                }
+
+                let spread = (tolerance / distance).asin();
+                low = low.max(angle - spread);
+                high = high.min(angle + spread);
+            }
+
+            reach = index;
+        }
+
+        reach
+    }).collect()
+}
+
+// prefix sums so the squared error of any side can be found without going over its points
+struct Sums
+{
+    x: Vec<f64>,
+    y: Vec<f64>,
+    xx: Vec<f64>,
+    xy: Vec<f64>,
+    yy: Vec<f64>
+}
+
+impl Sums
+{
+    pub fn new(points: &[Pos]) -> Self
+    {
+        let mut sums = Self{x: vec![0.0], y: vec![0.0], xx: vec![0.0], xy: vec![0.0], yy: vec![0.0]};
+
+        for point in points
+        {
+            let push = |sums: &mut Vec<f64>, value: f64| sums.push(sums.last().unwrap() + value);
+
+            push(&mut sums.x, point.x);
+            push(&mut sums.y, point.y);
+            push(&mut sums.xx, point.x * point.x);
+            push(&mut sums.xy, point.x * point.y);
+            push(&mut sums.yy, point.y * point.y);
+        }
+
+        sums
+    }
+
+    // sum of squared distances of the points from start to end to the line through them
+    pub fn penalty(&self, points: &[Pos], start: usize, end: usize) -> f64
+    {
+        let direction = points[end] - points[start];
+        let length = direction.magnitude();
+
+        if length == 0.0
+        {
+            return 0.0;
+        }
+
+        let (nx, ny) = (-direction.y / length, direction.x / length);
+        let c = nx * points[start].x + ny * points[start].y;
+
+        let range = |sums: &Vec<f64>| sums[end + 1] - sums[start];
+        let amount = (end + 1 - start) as f64;
+
+        let projected = nx * range(&self.x) + ny * range(&self.y);
+        let projected_squared = nx * nx * range(&self.xx)
+            + 2.0 * nx * ny * range(&self.xy)
+            + ny * ny * range(&self.yy);
+
+        (projected_squared - 2.0 * c * projected + amount * c * c).max(0.0)
+    }
+}
+
+// fewest sides that stay within tolerance of every point, the least squared error breaks ties
+fn optimal_polygon(points: &[Pos], tolerance: f64, max_span: usize) -> Vec<usize>
+{
+    let reaches = reaches(points, tolerance, max_span);
+    let sums = Sums::new(points);
+
+    let last = points.len() - 1;
+
+    let mut best: Vec<Option<(usize, f64, usize)>> = vec![None; points.len()];
+    best[0] = Some((0, 0.0, 0));
+
+    for start in 0..last
+    {
+        let (sides, penalty, _) = if let Some(best) = best[start]
+        {
+            best
+        } else
+        {
+            continue;
+        };
+
+        for (end, best_end) in best.iter_mut().enumerate().take(reaches[start] + 1).skip(start + 1)
+        {
+            let candidate = (sides + 1, penalty + sums.penalty(points, start, end), start);
+
+            let better = best_end.map(|(other_sides, other_penalty, _)|
+            {
+                (candidate.0, candidate.1) < (other_sides, other_penalty)
+            }).unwrap_or(true);
+
+            if better
+            {
+                *best_end = Some(candidate);
+            }
+        }
+    }
+
+    let mut vertices = vec![last];
+    while let Some(&vertex) = vertices.last()
+    {
+        if vertex == 0
+        {
+            break;
+        }
+
+        vertices.push(best[vertex].unwrap().2);
+    }
+
+    vertices.reverse();
+
+    vertices
+}
+
+// where the lines through a0 a1 and b0 b1 cross, none if theyre parallel
+fn intersection(a0: Pos, a1: Pos, b0: Pos, b1: Pos) -> Option<Pos>
+{
+    let (a, b) = (a1 - a0, b1 - b0);
+
+    let denominator = a.cross(b);
+    if denominator.abs() < 1e-12
+    {
+        return None;
+    }
+
+    Some(a0 + a * ((b0 - a0).cross(b) / denominator))
+}
+
+// 8 connected borders cut square corners with a short diagonal side, those get
+// replaced by the corner the sides around them would meet at
+fn merge_short_sides(mut vertices: Vec<Pos>, closed: bool, min_length: f64) -> Vec<Pos>
+{
+    // open ones have no side before the first or after the last to meet
+    let sides = |len: usize| if closed { len } else { len.saturating_sub(3) };
+
+    let mut index = 0;
+    while vertices.len() > 3 && index < sides(vertices.len())
+    {
+        let len = vertices.len();
+        let at = |offset: usize| vertices[(index + offset) % len];
+
+        let (previous, a, b, next) = (at(0), at(1), at(2), at(3));
+
+        let corner = ((b - a).magnitude() < min_length).then(||
+        {
+            intersection(previous, a, b, next)
+        }).flatten().filter(|corner|
+        {
+            (*corner - midpoint(a, b)).magnitude() < min_length
+        });
+
+        if let Some(corner) = corner
+        {
+            vertices[(index + 1) % len] = corner;
+            vertices.remove((index + 2) % len);
+        } else
+        {
+            index += 1;
+        }
+    }
+
+    vertices
+}
+
+fn midpoint(a: Pos, b: Pos) -> Pos
+{
+    a.lerp(b, 0.5)
+}
+
+// the segment around vertex going from the middle of the side before it to the middle of
+// the side after it, a corner if the vertex sticks out too far to round it off
+fn vertex_segments(previous: Pos, vertex: Pos, next: Pos, pixel: f64) -> Vec<Segment>
+{
+    let end = midpoint(vertex, next);
+
+    let chord = next - previous;
+    let height = (vertex - previous).cross(chord).abs() / chord.magnitude() / pixel;
+
+    let alpha = if height.is_finite()
+    {
+        (if height > 1.0 { 1.0 - 1.0 / height } else { 0.0 }) / 0.75
+    } else
+    {
+        4.0 / 3.0
+    };
+
+    if alpha >= ALPHA_MAX
+    {
+        return vec![Segment::Line(vertex), Segment::Line(end)];
+    }
+
+    let alpha = alpha.clamp(0.55, 1.0);
+
+    let towards = |from: Pos| from.lerp(vertex, 0.5 + 0.5 * alpha);
+
+    vec![Segment::Cubic(towards(midpoint(previous, vertex)), towards(end), end)]
+}
+
+// potrace style vectorisation of a traced pixel border, pixel is the size of one
+// pixel in curve coordinates
+pub fn fit_path(curve: &Curve, pixel: f64) -> BezierPath
+{
+    let closed = curve.is_closed();
+
+    let mut points = curve.points().to_vec();
+    if points.len() < 3 || (closed && points.len() < 4)
+    {
+        return BezierPath::from_curve(curve);
+    }
+
+    let max_span = if closed
+    {
+        // loops need at least three sides
+        points.push(points[0]);
+
+        (points.len() - 2) / 3
+    } else
+    {
+        points.len()
+    };
+
+    let mut vertices = optimal_polygon(&points, STRAIGHT_TOLERANCE * pixel, max_span.max(1))
+        .into_iter()
+        .map(|index| points[index])
+        .collect::<Vec<_>>();
+
+    if closed
+    {
+        vertices.pop();
+    }
+
+    let vertices = merge_short_sides(vertices, closed, SHORT_SIDE * pixel);
+
+    let segments_around = |index: usize, len: usize|
+    {
+        let previous = vertices[(index + len - 1) % len];
+        let next = vertices[(index + 1) % len];
+
+        vertex_segments(previous, vertices[index], next, pixel)
+    };
+
+    let mut segments = Vec::new();
+
+    let start = if closed
+    {
+        let len = vertices.len();
+
+        for index in 0..len
+        {
+            segments.extend(segments_around(index, len));
+        }
+
+        midpoint(vertices[len - 1], vertices[0])
+    } else
+    {
+        let last = vertices.len() - 1;
+
+        segments.push(Segment::Line(midpoint(vertices[0], vertices[1.min(last)])));
+        for index in 1..last
+        {
+            segments.extend(segments_around(index, vertices.len()));
+        }
+
+        segments.push(Segment::Line(vertices[last]));
+
+        vertices[0]
+    };
+
+    BezierPath{start, segments, closed}
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn traced_circle_stays_round()
+    {
+        let (center, radius) = (Pos::new(0.5, 0.5), 0.3);
+        let pixel = 0.01;
+
+        let circle = Curve::new_closed((0..188).map(|index|
+        {
+            let angle = index as f64 / 188.0 * std::f64::consts::TAU;
+
+            // snapped to the pixel grid like a traced border
+            let pos = center + Pos::new(angle.cos(), angle.sin()) * radius;
+            Pos::new((pos.x / pixel).round() * pixel, (pos.y / pixel).round() * pixel)
+        }).collect());
+
+        let flattened = fit_path(&circle, pixel).flatten(pixel * 0.1);
+
+        assert!(flattened.is_closed());
+        assert_ne!(flattened.points().last(), Some(&flattened.start()));
+
+        for pos in flattened.points()
+        {
+            assert!(((*pos - center).magnitude() - radius).abs() < pixel * 1.5, "{pos:?}");
+        }
+    }
+}
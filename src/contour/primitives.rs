@@ -0,0 +1,365 @@
+use std::{
+    fmt,
+    f64::consts::TAU
+};
+
+use super::{Curve, Pos};
+
+
+const MIN_SEGMENT_POINTS: usize = 3;
+const MIN_CIRCLE_POINTS: usize = 8;
+const MIN_ELLIPSE_POINTS: usize = 10;
+
+// very flat ellipses are usually two lines close together, not an ellipse
+const MIN_ELLIPSE_RATIO: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Primitive
+{
+    Segment{start: Pos, end: Pos},
+    Circle{center: Pos, radius: f64},
+    Ellipse{center: Pos, radii: (f64, f64), angle: f64}
+}
+
+impl fmt::Display for Primitive
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            Self::Segment{start, end} =>
+            {
+                write!(f, "segment from ({:.3}, {:.3}) to ({:.3}, {:.3})", start.x, start.y, end.x, end.y)
+            },
+            Self::Circle{center, radius} =>
+            {
+                write!(f, "circle at ({:.3}, {:.3}) with radius {radius:.3}", center.x, center.y)
+            },
+            Self::Ellipse{center, radii: (a, b), angle} =>
+            {
+                write!(
+                    f,
+                    "ellipse at ({:.3}, {:.3}) with radii {a:.3} and {b:.3} rotated {:.1} degrees",
+                    center.x,
+                    center.y,
+                    angle.to_degrees()
+                )
+            }
+        }
+    }
+}
+
+impl Primitive
+{
+    // enough points that the polygon stays within a quarter of the tolerance from the shape
+    fn steps(radius: f64, tolerance: f64) -> usize
+    {
+        let max_angle = 2.0 * (1.0 - (tolerance / (4.0 * radius)).min(1.0)).acos();
+
+        ((TAU / max_angle).ceil() as usize).max(8)
+    }
+
+    pub fn to_curve(self, tolerance: f64) -> Curve
+    {
+        match self
+        {
+            Self::Segment{start, end} => Curve::new(vec![start, end]),
+            Self::Circle{center, radius} =>
+            {
+                let steps = Self::steps(radius, tolerance);
+
+                Curve::new_closed((0..steps).map(|step|
+                {
+                    let angle = TAU * step as f64 / steps as f64;
+
+                    center + Pos::new(angle.cos(), angle.sin()) * radius
+                }).collect())
+            },
+            Self::Ellipse{center, radii: (a, b), angle} =>
+            {
+                let steps = Self::steps(a.max(b), tolerance);
+                let (sin, cos) = angle.sin_cos();
+
+                Curve::new_closed((0..steps).map(|step|
+                {
+                    let t = TAU * step as f64 / steps as f64;
+                    let (x, y) = (a * t.cos(), b * t.sin());
+
+                    center + Pos::new(x * cos - y * sin, x * sin + y * cos)
+                }).collect())
+            }
+        }
+    }
+}
+
+// gaussian elimination with partial pivoting, none if the system is singular
+fn solve(mut matrix: Vec<Vec<f64>>, mut rhs: Vec<f64>) -> Option<Vec<f64>>
+{
+    let size = rhs.len();
+
+    for column in 0..size
+    {
+        let pivot = (column..size).max_by(|a, b| matrix[*a][column].abs().total_cmp(&matrix[*b][column].abs()))?;
+        if matrix[pivot][column].abs() < 1e-12
+        {
+            return None;
+        }
+
+        matrix.swap(column, pivot);
+        rhs.swap(column, pivot);
+
+        let pivot_row = matrix[column].clone();
+        for row in (column + 1)..size
+        {
+            let factor = matrix[row][column] / pivot_row[column];
+
+            matrix[row].iter_mut().zip(&pivot_row).skip(column).for_each(|(value, pivot)|
+            {
+                *value -= factor * pivot;
+            });
+
+            rhs[row] -= factor * rhs[column];
+        }
+    }
+
+    let mut solution = vec![0.0; size];
+    for row in (0..size).rev()
+    {
+        let known: f64 = ((row + 1)..size).map(|i| matrix[row][i] * solution[i]).sum();
+
+        solution[row] = (rhs[row] - known) / matrix[row][row];
+    }
+
+    Some(solution)
+}
+
+// least squares for rows of terms that should add up to the target
+fn least_squares(rows: impl Iterator<Item=(Vec<f64>, f64)>, size: usize) -> Option<Vec<f64>>
+{
+    let mut normal = vec![vec![0.0; size]; size];
+    let mut rhs = vec![0.0; size];
+
+    for (terms, target) in rows
+    {
+        for i in 0..size
+        {
+            for j in 0..size
+            {
+                normal[i][j] += terms[i] * terms[j];
+            }
+
+            rhs[i] += terms[i] * target;
+        }
+    }
+
+    solve(normal, rhs)
+}
+
+fn mean_point(points: &[Pos]) -> Pos
+{
+    let sum = points.iter().fold(Pos::new(0.0, 0.0), |sum, point| sum + *point);
+
+    sum * (1.0 / points.len() as f64)
+}
+
+// total least squares line, the ends are the first and last points moved onto it
+fn fit_segment(points: &[Pos], tolerance: f64) -> Option<Primitive>
+{
+    let center = mean_point(points);
+
+    let (mut xx, mut xy, mut yy) = (0.0, 0.0, 0.0);
+    for point in points
+    {
+        let d = *point - center;
+
+        xx += d.x * d.x;
+        xy += d.x * d.y;
+        yy += d.y * d.y;
+    }
+
+    let angle = 0.5 * (2.0 * xy).atan2(xx - yy);
+    let direction = Pos::new(angle.cos(), angle.sin());
+
+    let fits = points.iter().all(|point|
+    {
+        let d = *point - center;
+
+        d.cross(direction).abs() <= tolerance
+    });
+
+    let project = |point: Pos|
+    {
+        let d = point - center;
+
+        center + direction * d.dot(direction)
+    };
+
+    fits.then(|| Primitive::Segment{start: project(points[0]), end: project(points[points.len() - 1])})
+}
+
+// center of the enclosed area and average distance from it, fits work on points scaled around 1 so
+// tiny shapes dont end up with near zero terms
+// unlike the mean of the points the area center doesnt move towards where the border has more points
+fn normalization(curve: &Curve) -> (Pos, f64)
+{
+    let center = curve.centroid();
+    let points = curve.points();
+
+    let spread = points.iter().map(|point| (*point - center).magnitude().powi(2)).sum::<f64>()
+        / points.len() as f64;
+
+    (center, spread.sqrt())
+}
+
+// kasa fit of x^2 + y^2 + dx + ey + f = 0
+fn fit_circle(curve: &Curve, tolerance: f64) -> Option<Primitive>
+{
+    let points = curve.points();
+    let (offset, spread) = normalization(curve);
+
+    let rows = points.iter().map(|point|
+    {
+        let p = (*point - offset) * (1.0 / spread);
+
+        (vec![p.x, p.y, 1.0], -p.dot(p))
+    });
+
+    let solution = least_squares(rows, 3)?;
+    let (d, e, f) = (solution[0], solution[1], solution[2]);
+
+    let center = Pos::new(-d / 2.0, -e / 2.0);
+    let radius = (center.dot(center) - f).sqrt() * spread;
+
+    let center = offset + center * spread;
+
+    let fits = radius.is_finite() && points.iter().all(|point|
+    {
+        ((*point - center).magnitude() - radius).abs() <= tolerance
+    });
+
+    fits.then_some(Primitive::Circle{center, radius})
+}
+
+// conic ax^2 + bxy + cy^2 + dx + ey = 1 around the area center, which is inside any ellipse
+fn fit_ellipse(curve: &Curve, tolerance: f64) -> Option<Primitive>
+{
+    let points = curve.points();
+    let (offset, spread) = normalization(curve);
+
+    let rows = points.iter().map(|point|
+    {
+        let p = (*point - offset) * (1.0 / spread);
+
+        (vec![p.x * p.x, p.x * p.y, p.y * p.y, p.x, p.y], 1.0)
+    });
+
+    let solution = least_squares(rows, 5)?;
+    let (a, b, c, d, e) = (solution[0], solution[1], solution[2], solution[3], solution[4]);
+
+    if b * b - 4.0 * a * c >= 0.0
+    {
+        return None;
+    }
+
+    let center = solve(vec![vec![2.0 * a, b], vec![b, 2.0 * c]], vec![-d, -e])?;
+    let (x0, y0) = (center[0], center[1]);
+
+    // whats left of the constant once the conic is moved to its center
+    let scale = 1.0 - (a * x0 * x0 + b * x0 * y0 + c * y0 * y0 + d * x0 + e * y0);
+
+    let angle = 0.5 * b.atan2(a - c);
+    let (sin, cos) = angle.sin_cos();
+
+    let along = a * cos * cos + b * sin * cos + c * sin * sin;
+    let across = a * sin * sin - b * sin * cos + c * cos * cos;
+
+    let radii = ((scale / along).sqrt() * spread, (scale / across).sqrt() * spread);
+    if !radii.0.is_finite() || !radii.1.is_finite() || radii.0.min(radii.1) < radii.0.max(radii.1) * MIN_ELLIPSE_RATIO
+    {
+        return None;
+    }
+
+    let center = offset + Pos::new(x0, y0) * spread;
+
+    // distance along the ray from the center, close to the real distance for round ellipses
+    let fits = points.iter().all(|point|
+    {
+        let p = *point - center;
+        let (x, y) = (p.x * cos + p.y * sin, -p.x * sin + p.y * cos);
+
+        let reach = ((x / radii.0).powi(2) + (y / radii.1).powi(2)).sqrt();
+
+        (p.magnitude() * (1.0 - 1.0 / reach)).abs() <= tolerance
+    });
+
+    fits.then_some(Primitive::Ellipse{center, radii, angle})
+}
+
+// the simplest shape the whole curve stays within tolerance of, if any
+pub fn recognise(curve: &Curve, tolerance: f64) -> Option<Primitive>
+{
+    let points = curve.points();
+
+    if curve.is_closed()
+    {
+        (points.len() >= MIN_CIRCLE_POINTS).then(|| fit_circle(curve, tolerance)).flatten()
+            .or_else(||
+            {
+                (points.len() >= MIN_ELLIPSE_POINTS).then(|| fit_ellipse(curve, tolerance)).flatten()
+            })
+    } else
+    {
+        (points.len() >= MIN_SEGMENT_POINTS).then(|| fit_segment(points, tolerance)).flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    // points around an ellipse, three times as dense on the top half
+    fn uneven_ellipse(center: Pos, radii: (f64, f64)) -> Curve
+    {
+        let angles = (0..90).map(|index| index as f64 / 90.0 * std::f64::consts::PI)
+            .chain((0..30).map(|index| (1.0 + index as f64 / 30.0) * std::f64::consts::PI));
+
+        Curve::new_closed(angles.map(|angle|
+        {
+            center + Pos::new(angle.cos() * radii.0, angle.sin() * radii.1)
+        }).collect())
+    }
+
+    #[test]
+    fn unevenly_spaced_circle_is_found()
+    {
+        let center = Pos::new(0.4, 0.6);
+
+        match recognise(&uneven_ellipse(center, (0.2, 0.2)), 0.001)
+        {
+            Some(Primitive::Circle{center: found, radius}) =>
+            {
+                assert!((found - center).magnitude() < 1e-6);
+                assert!((radius - 0.2).abs() < 1e-6);
+            },
+            other => panic!("{other:?}")
+        }
+    }
+
+    #[test]
+    fn unevenly_spaced_ellipse_is_found()
+    {
+        let center = Pos::new(0.5, 0.5);
+
+        match recognise(&uneven_ellipse(center, (0.3, 0.1)), 0.001)
+        {
+            Some(Primitive::Ellipse{center: found, radii, ..}) =>
+            {
+                assert!((found - center).magnitude() < 1e-6);
+                assert!((radii.0.max(radii.1) - 0.3).abs() < 1e-6);
+                assert!((radii.0.min(radii.1) - 0.1).abs() < 1e-6);
+            },
+            other => panic!("{other:?}")
+        }
+    }
+}
@@ -1,49 +1,308 @@
+use std::{
+    str::FromStr,
+    cmp::Ordering,
+    collections::BinaryHeap
+};
+
 use super::{Curve, Pos};
 
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm
+{
+    DouglasPeucker,
+    Visvalingam,
+    ReumannWitkam
+}
+
+impl FromStr for Algorithm
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        match s.to_lowercase().as_str()
+        {
+            "dp" | "douglas-peucker" => Ok(Self::DouglasPeucker),
+            "vw" | "visvalingam" => Ok(Self::Visvalingam),
+            "rw" | "reumann-witkam" => Ok(Self::ReumannWitkam),
+            _ => Err(format!("unknown simplification algorithm: {s}"))
+        }
+    }
+}
+
+
 fn line_distance(point: Pos, p0: Pos, p1: Pos) -> f64
 {
     let pdiff = p0 - point;
     let diff = p1 - p0;
 
     let line_distance = diff.magnitude();
-    let triangle_area = (diff.x * pdiff.y - pdiff.x * diff.y).abs();
+    if line_distance == 0.0
+    {
+        return pdiff.magnitude();
+    }
+
+    let triangle_area = diff.cross(pdiff).abs();
 
     triangle_area / line_distance
 }
 
+// marks the points douglas peucker keeps between start and end (both already kept)
+// indices past the end wrap around so closed curves can be split anywhere
+// like the original recursive version a split keeps the farthest point and the one before it
+fn douglas_peucker(curve: &Curve, keep: &mut [bool], start: usize, end: usize, epsilon: f64)
+{
+    let len = curve.len();
+
+    let mut stack = vec![(start, end)];
+    while let Some((start, end)) = stack.pop()
+    {
+        let (p0, p1) = (curve[start % len], curve[end % len]);
+
+        let mut dmax = 0.0;
+        let mut index = start;
+
+        for i in (start + 1)..end
+        {
+            let d = line_distance(curve[i % len], p0, p1);
+            if d > dmax
+            {
+                index = i;
+                dmax = d;
+            }
+        }
+
+        if dmax > epsilon
+        {
+            keep[(index - 1) % len] = true;
+            keep[index % len] = true;
+
+            stack.push((index, end));
+            stack.push((start, index - 1));
+        }
+    }
+}
+
+fn kept_points(curve: Curve, keep: &[bool]) -> Vec<Pos>
+{
+    curve.into_iter().zip(keep).filter_map(|(pos, keep)| keep.then_some(pos)).collect()
+}
+
 pub fn simplify_curve(curve: Curve, epsilon: f64) -> Curve
 {
-    let mut dmax = 0.0;
-    let mut index = 0;
+    if curve.len() <= 2
+    {
+        return curve;
+    }
 
     let last = curve.len() - 1;
 
-    for i in 1..curve.len()
+    let mut keep = vec![false; curve.len()];
+    keep[0] = true;
+    keep[last] = true;
+
+    douglas_peucker(&curve, &mut keep, 0, last, epsilon);
+
+    Curve::new(kept_points(curve, &keep))
+}
+
+pub fn farthest_from(curve: &Curve, index: usize) -> usize
+{
+    (0..curve.len()).max_by(|a, b|
+    {
+        let a = (curve[*a] - curve[index]).magnitude();
+        let b = (curve[*b] - curve[index]).magnitude();
+
+        a.total_cmp(&b)
+    }).unwrap()
+}
+
+// loops get split at their two most distant points so neither half
+// depends on where the tracing happened to start
+pub fn simplify_closed(curve: Curve, epsilon: f64) -> Curve
+{
+    if curve.len() <= 3
+    {
+        return curve;
+    }
+
+    let start = farthest_from(&curve, 0);
+    let end = farthest_from(&curve, start);
+
+    let (start, end) = (start.min(end), start.max(end));
+
+    let mut keep = vec![false; curve.len()];
+    keep[start] = true;
+    keep[end] = true;
+
+    douglas_peucker(&curve, &mut keep, start, end, epsilon);
+    douglas_peucker(&curve, &mut keep, end, start + curve.len(), epsilon);
+
+    Curve::new_closed(kept_points(curve, &keep))
+}
+
+fn triangle_area(a: Pos, b: Pos, c: Pos) -> f64
+{
+    (b - a).cross(c - a).abs() / 2.0
+}
+
+struct Candidate
+{
+    area: f64,
+    index: usize,
+    version: usize
+}
+
+impl PartialEq for Candidate
+{
+    fn eq(&self, other: &Self) -> bool
+    {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering>
+    {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate
+{
+    // reversed so the heap pops the smallest area first
+    fn cmp(&self, other: &Self) -> Ordering
+    {
+        other.area.total_cmp(&self.area).then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+// removes the point making the smallest triangle with its neighbors until every
+// triangle is at least epsilon squared big
+pub fn visvalingam(curve: Curve, epsilon: f64) -> Curve
+{
+    let closed = curve.is_closed();
+    let len = curve.len();
+
+    let min_points = if closed { 3 } else { 2 };
+    if len <= min_points
     {
-        let d = line_distance(curve[i], curve[0], curve[last]);
-        if d > dmax
+        return curve;
+    }
+
+    let min_area = epsilon * epsilon;
+
+    let mut previous = (0..len).map(|index|
+    {
+        if index == 0 { closed.then_some(len - 1) } else { Some(index - 1) }
+    }).collect::<Vec<_>>();
+
+    let mut next = (0..len).map(|index|
+    {
+        if index == len - 1 { closed.then_some(0) } else { Some(index + 1) }
+    }).collect::<Vec<_>>();
+
+    let area_of = |previous: &[Option<usize>], next: &[Option<usize>], index: usize|
+    {
+        Some(triangle_area(curve[previous[index]?], curve[index], curve[next[index]?]))
+    };
+
+    let mut versions = vec![0; len];
+    let mut removed = vec![false; len];
+
+    let mut heap = (0..len).filter_map(|index|
+    {
+        area_of(&previous, &next, index).map(|area| Candidate{area, index, version: 0})
+    }).collect::<BinaryHeap<_>>();
+
+    let mut remaining = len;
+    while let Some(Candidate{area, index, version}) = heap.pop()
+    {
+        if removed[index] || version != versions[index]
+        {
+            continue;
+        }
+
+        if area >= min_area || remaining <= min_points
+        {
+            break;
+        }
+
+        removed[index] = true;
+        remaining -= 1;
+
+        let (before, after) = (previous[index].unwrap(), next[index].unwrap());
+        next[before] = Some(after);
+        previous[after] = Some(before);
+
+        for neighbor in [before, after]
         {
-            index = i;
-            dmax = d;
+            if let Some(neighbor_area) = area_of(&previous, &next, neighbor)
+            {
+                versions[neighbor] += 1;
+
+                // a point never becomes cheaper to remove than the one removed before it
+                heap.push(Candidate{
+                    area: neighbor_area.max(area),
+                    index: neighbor,
+                    version: versions[neighbor]
+                });
+            }
         }
     }
 
-    if dmax > epsilon
+    let keep = removed.into_iter().map(|removed| !removed).collect::<Vec<_>>();
+    let points = kept_points(curve, &keep);
+
+    Curve::from_points(points, closed)
+}
+
+// streams through the points keeping the last one inside an epsilon wide
+// corridor around the line through the current key point and the one after it
+pub fn reumann_witkam(curve: Curve, epsilon: f64) -> Curve
+{
+    if curve.len() <= 2
     {
-        let mut recursive_one = simplify_curve(curve.part(0, index), epsilon);
-        let mut recursive_two = simplify_curve(curve.part(index, curve.len()), epsilon);
+        return curve;
+    }
 
-        recursive_one.append(&mut recursive_two);
+    let closed = curve.is_closed();
+    let last = curve.len() - 1;
+
+    let mut keep = vec![false; curve.len()];
+    keep[0] = true;
+    keep[last] = true;
 
-        recursive_one
-    } else
+    let mut key = 0;
+    while key + 1 < last
     {
-        Curve::new(vec![curve[0], curve[last]])
+        let (p0, p1) = (curve[key], curve[key + 1]);
+
+        let mut index = key + 2;
+        while index <= last && line_distance(curve[index], p0, p1) <= epsilon
+        {
+            index += 1;
+        }
+
+        if index > last
+        {
+            break;
+        }
+
+        key = index - 1;
+        keep[key] = true;
     }
+
+    let points = kept_points(curve, &keep);
+
+    Curve::from_points(points, closed)
 }
 
-pub fn simplify_borders(curves: Vec<Curve>, epsilon: f64) -> Vec<Curve>
+pub fn simplify_borders(curves: Vec<Curve>, epsilon: f64, algorithm: Algorithm) -> Vec<Curve>
 {
     if epsilon < 0.0
     {
@@ -52,6 +311,94 @@ pub fn simplify_borders(curves: Vec<Curve>, epsilon: f64) -> Vec<Curve>
 
     curves.into_iter().map(|curve|
     {
-        simplify_curve(curve, epsilon)
+        match algorithm
+        {
+            Algorithm::DouglasPeucker if curve.is_closed() => simplify_closed(curve, epsilon),
+            Algorithm::DouglasPeucker => simplify_curve(curve, epsilon),
+            Algorithm::Visvalingam => visvalingam(curve, epsilon),
+            Algorithm::ReumannWitkam => reumann_witkam(curve, epsilon)
+        }
     }).collect()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::contour::test_square;
+
+    fn sine_wave(amount: usize) -> Curve
+    {
+        Curve::new((0..amount).map(|index|
+        {
+            let x = index as f64 / (amount - 1) as f64;
+
+            Pos::new(x, (x * std::f64::consts::TAU).sin() * 0.25)
+        }).collect())
+    }
+
+    // the recursive version simplify_curve replaced
+    fn recursive(points: &[Pos], epsilon: f64) -> Vec<Pos>
+    {
+        let last = points.len() - 1;
+
+        let (index, dmax) = (1..points.len()).fold((0, 0.0), |(index, dmax), i|
+        {
+            let d = line_distance(points[i], points[0], points[last]);
+
+            if d > dmax { (i, d) } else { (index, dmax) }
+        });
+
+        if dmax > epsilon
+        {
+            let mut first = recursive(&points[..index], epsilon);
+            first.extend(recursive(&points[index..], epsilon));
+
+            first
+        } else
+        {
+            vec![points[0], points[last]]
+        }
+    }
+
+    #[test]
+    fn douglas_peucker_matches_recursive()
+    {
+        for (amount, epsilon) in [(40, 0.05), (40, 0.01), (200, 0.002), (7, 0.1)]
+        {
+            let curve = sine_wave(amount);
+
+            let mut expected = recursive(curve.points(), epsilon);
+            expected.dedup();
+
+            assert_eq!(simplify_curve(curve, epsilon).points(), expected.as_slice());
+        }
+    }
+
+    #[test]
+    fn straight_line_keeps_its_ends()
+    {
+        let curve = Curve::new((0..10).map(|index| Pos::new(index as f64, 0.0)).collect());
+
+        for algorithm in [Algorithm::DouglasPeucker, Algorithm::Visvalingam, Algorithm::ReumannWitkam]
+        {
+            let simplified = simplify_borders(vec![curve.clone()], 0.1, algorithm).remove(0);
+
+            assert_eq!(simplified.points(), &[Pos::new(0.0, 0.0), Pos::new(9.0, 0.0)]);
+        }
+    }
+
+    #[test]
+    fn closed_curves_stay_closed()
+    {
+        let square = test_square(true);
+
+        for algorithm in [Algorithm::DouglasPeucker, Algorithm::Visvalingam, Algorithm::ReumannWitkam]
+        {
+            let simplified = simplify_borders(vec![square.clone()], 0.01, algorithm).remove(0);
+
+            assert!(simplified.is_closed());
+            assert!(simplified.len() >= 4 && simplified.len() <= 6, "{algorithm:?}: {}", simplified.len());
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use std::str::FromStr;
+
+use super::{Curve, Pos};
+
+
+// chaikin stops early once segments are short enough, this caps it for long ones
+const MAX_CHAIKIN_ITERATIONS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Smoothing
+{
+    Chaikin,
+    CatmullRom
+}
+
+impl FromStr for Smoothing
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        match s.to_lowercase().as_str()
+        {
+            "chaikin" => Ok(Self::Chaikin),
+            "catmull-rom" | "catmull" => Ok(Self::CatmullRom),
+            _ => Err(format!("unknown smoothing: {s}"))
+        }
+    }
+}
+
+// indices of the points where the curve turns sharper than max_angle
+fn corners(points: &[Pos], closed: bool, max_angle: f64) -> Vec<usize>
+{
+    let len = points.len();
+
+    (0..len).filter(|&index|
+    {
+        if !closed && (index == 0 || index == len - 1)
+        {
+            return false;
+        }
+
+        let previous = points[(index + len - 1) % len];
+        let next = points[(index + 1) % len];
+
+        points[index].turning_angle(previous, next) > max_angle
+    }).collect()
+}
+
+fn longest_segment(points: &[Pos], closed: bool) -> f64
+{
+    let wrap = closed.then(|| (points[0] - points[points.len() - 1]).magnitude());
+
+    points.windows(2).map(|pair| (pair[1] - pair[0]).magnitude())
+        .chain(wrap)
+        .fold(0.0, f64::max)
+}
+
+// cuts every corner of a polyline, the ends of open ones stay where they are
+fn chaikin(mut points: Vec<Pos>, closed: bool, step: f64) -> Vec<Pos>
+{
+    for _ in 0..MAX_CHAIKIN_ITERATIONS
+    {
+        if points.len() < 3 || longest_segment(&points, closed) <= step
+        {
+            break;
+        }
+
+        let len = points.len();
+        let segments = if closed { len } else { len - 1 };
+
+        let mut cut = Vec::with_capacity(segments * 2 + 2);
+        if !closed
+        {
+            cut.push(points[0]);
+        }
+
+        cut.extend((0..segments).flat_map(|index|
+        {
+            let (a, b) = (points[index], points[(index + 1) % len]);
+
+            [a.lerp(b, 0.25), a.lerp(b, 0.75)]
+        }));
+
+        if !closed
+        {
+            cut.push(points[len - 1]);
+        }
+
+        points = cut;
+    }
+
+    points
+}
+
+fn catmull_rom_point(p0: Pos, p1: Pos, p2: Pos, p3: Pos, t: f64) -> Pos
+{
+    let (t2, t3) = (t * t, t * t * t);
+
+    (p1 * 2.0
+        + (p2 - p0) * t
+        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3) * 0.5
+}
+
+// spline through every point, resampled so segments are around step long
+fn catmull_rom(points: Vec<Pos>, closed: bool, step: f64) -> Vec<Pos>
+{
+    if points.len() < 3
+    {
+        return points;
+    }
+
+    let len = points.len() as isize;
+    let last = points.len() - 1;
+
+    // mirrored points past the ends of open curves keep the spline heading straight out of them
+    let point = |index: isize| -> Pos
+    {
+        if closed
+        {
+            points[index.rem_euclid(len) as usize]
+        } else if index < 0
+        {
+            points[0] * 2.0 - points[1]
+        } else if index >= len
+        {
+            points[last] * 2.0 - points[last - 1]
+        } else
+        {
+            points[index as usize]
+        }
+    };
+
+    let segments = if closed { len } else { len - 1 };
+
+    let mut smoothed = Vec::new();
+    for index in 0..segments
+    {
+        let (p0, p1, p2, p3) = (point(index - 1), point(index), point(index + 1), point(index + 2));
+
+        let steps = ((p2 - p1).magnitude() / step).ceil().max(1.0) as usize;
+
+        smoothed.extend((0..steps).map(|i| catmull_rom_point(p0, p1, p2, p3, i as f64 / steps as f64)));
+    }
+
+    if !closed
+    {
+        smoothed.push(points[last]);
+    }
+
+    smoothed
+}
+
+fn smooth_section(points: Vec<Pos>, closed: bool, smoothing: Smoothing, step: f64) -> Vec<Pos>
+{
+    match smoothing
+    {
+        Smoothing::Chaikin => chaikin(points, closed, step),
+        Smoothing::CatmullRom => catmull_rom(points, closed, step)
+    }
+}
+
+// smooths the curve between its corners, corners sharper than max_angle stay sharp
+pub fn smooth_curve(curve: Curve, smoothing: Smoothing, step: f64, max_angle: f64) -> Curve
+{
+    if curve.len() < 3
+    {
+        return curve;
+    }
+
+    let closed = curve.is_closed();
+    let mut points = curve.into_iter().collect::<Vec<_>>();
+
+    let mut corners = corners(&points, closed, max_angle);
+
+    if closed
+    {
+        if corners.is_empty()
+        {
+            return Curve::new_closed(smooth_section(points, true, smoothing, step));
+        }
+
+        // start and end at a corner so the loop can be smoothed like an open curve
+        let start = corners[0];
+
+        points.rotate_left(start);
+        corners.iter_mut().for_each(|corner| *corner -= start);
+
+        points.push(points[0]);
+    } else
+    {
+        corners.insert(0, 0);
+    }
+
+    corners.push(points.len() - 1);
+    corners.dedup();
+
+    let mut smoothed: Vec<Pos> = Vec::new();
+    for pair in corners.windows(2)
+    {
+        let section = smooth_section(points[pair[0]..=pair[1]].to_vec(), false, smoothing, step);
+
+        // sections share their corner point
+        let skip = if smoothed.is_empty() { 0 } else { 1 };
+        smoothed.extend(section.into_iter().skip(skip));
+    }
+
+    if closed
+    {
+        smoothed.pop();
+    }
+
+    Curve::from_points(smoothed, closed)
+}
+
+pub fn smooth_curves(curves: Vec<Curve>, smoothing: Smoothing, step: f64, max_angle: f64) -> Vec<Curve>
+{
+    if step <= 0.0
+    {
+        panic!("invalid smoothing step");
+    }
+
+    curves.into_iter().map(|curve| smooth_curve(curve, smoothing, step, max_angle)).collect()
+}
@@ -138,9 +138,21 @@ impl LineDrawer
         outputs.lines().filter_map(|x| x.trim().parse().ok()).collect()
     }
 
-    pub fn draw_curve(&self, mut curve: impl Iterator<Item=Pos>)
+    pub fn draw_curve(&self, mut curve: impl Iterator<Item=Pos>, closed: bool)
     {
-        self.mouse_move(curve.next().unwrap());
+        let first = curve.next().unwrap();
+
+        let second = if let Some(second) = curve.next()
+        {
+            second
+        } else
+        {
+            self.click(first);
+
+            return;
+        };
+
+        self.mouse_move(first);
 
         thread::sleep(self.delay);
 
@@ -148,7 +160,7 @@ impl LineDrawer
 
         thread::sleep(self.move_delay);
 
-        self.mouse_move(curve.next().unwrap());
+        self.mouse_move(second);
 
         curve.for_each(|point|
         {
@@ -157,6 +169,28 @@ impl LineDrawer
             self.mouse_move(point);
         });
 
+        if closed
+        {
+            thread::sleep(self.move_delay);
+
+            self.mouse_move(first);
+        }
+
+        thread::sleep(self.move_delay);
+
+        self.mouse_up();
+
+        thread::sleep(self.delay);
+    }
+
+    pub fn click(&self, point: Pos)
+    {
+        self.mouse_move(point);
+
+        thread::sleep(self.delay);
+
+        self.mouse_down();
+
         thread::sleep(self.move_delay);
 
         self.mouse_up();
@@ -200,12 +234,23 @@ impl LineDrawer
             .unwrap().wait();
     }
 
-    pub fn mouse_move(&self, point: Pos)
+    pub fn size(&self) -> (f64, f64)
     {
-        let (x, y) = (
+        (self.width, self.height)
+    }
+
+    // the screen pixel the mouse ends up on for a point
+    pub fn screen_pixel(&self, point: Pos) -> (usize, usize)
+    {
+        (
             (point.x * self.width + self.window_x) as usize,
             (point.y * self.height + self.window_y) as usize
-        );
+        )
+    }
+
+    pub fn mouse_move(&self, point: Pos)
+    {
+        let (x, y) = self.screen_pixel(point);
 
         if self.verbose
         {
@@ -0,0 +1,351 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    FloatImage,
+    contour::{Curve, Pos}
+};
+
+
+// a run of pixels between two junctions or endpoints
+struct Branch
+{
+    start: usize,
+    end: usize,
+    pixels: Vec<usize>,
+    // pen lifts added to pair up odd vertices, not drawn
+    jump: bool
+}
+
+struct PixelGraph
+{
+    width: usize,
+    neighbors: HashMap<usize, Vec<usize>>
+}
+
+impl PixelGraph
+{
+    pub fn new(image: &FloatImage, threshold: f64) -> Self
+    {
+        let (width, height) = (image.width() as i64, image.height() as i64);
+
+        let on = |x: i64, y: i64|
+        {
+            (0..width).contains(&x) && (0..height).contains(&y)
+                && image.get(x as usize, y as usize).unwrap() > threshold
+        };
+
+        let mut neighbors = HashMap::new();
+        for y in 0..height
+        {
+            for x in 0..width
+            {
+                if !on(x, y)
+                {
+                    continue;
+                }
+
+                let connected = (-1..=1).flat_map(|d_y| (-1..=1).map(move |d_x| (d_x, d_y)))
+                    .filter(|&(d_x, d_y)|
+                    {
+                        if (d_x, d_y) == (0, 0) || !on(x + d_x, y + d_y)
+                        {
+                            return false;
+                        }
+
+                        // diagonals already connected through a shared neighbor would make triangles
+                        let diagonal = d_x != 0 && d_y != 0;
+                        !(diagonal && (on(x + d_x, y) || on(x, y + d_y)))
+                    })
+                    .map(|(d_x, d_y)| ((y + d_y) * width + x + d_x) as usize)
+                    .collect::<Vec<_>>();
+
+                neighbors.insert((y * width + x) as usize, connected);
+            }
+        }
+
+        Self{width: image.width(), neighbors}
+    }
+
+    fn degree(&self, pixel: usize) -> usize
+    {
+        self.neighbors[&pixel].len()
+    }
+
+    fn walk(
+        &self,
+        start: usize,
+        first: usize,
+        used: &mut HashSet<(usize, usize)>
+    ) -> Vec<usize>
+    {
+        let edge = |a: usize, b: usize| (a.min(b), a.max(b));
+
+        let mut pixels = vec![start];
+        used.insert(edge(start, first));
+
+        let mut current = first;
+        loop
+        {
+            pixels.push(current);
+
+            if current == start || self.degree(current) != 2
+            {
+                break;
+            }
+
+            let next = self.neighbors[&current].iter().copied().find(|next|
+            {
+                !used.contains(&edge(current, *next))
+            });
+
+            if let Some(next) = next
+            {
+                used.insert(edge(current, next));
+                current = next;
+            } else
+            {
+                break;
+            }
+        }
+
+        pixels
+    }
+
+    pub fn branches(&self) -> Vec<Branch>
+    {
+        let mut pixels = self.neighbors.keys().copied().collect::<Vec<_>>();
+        pixels.sort_unstable();
+
+        let mut used = HashSet::new();
+        let mut branches = Vec::new();
+
+        let mut add_branches_from = |start: usize, used: &mut HashSet<(usize, usize)>|
+        {
+            for &first in &self.neighbors[&start]
+            {
+                if used.contains(&(start.min(first), start.max(first)))
+                {
+                    continue;
+                }
+
+                let pixels = self.walk(start, first, used);
+                let end = *pixels.last().unwrap();
+
+                branches.push(Branch{start, end, pixels, jump: false});
+            }
+        };
+
+        for &pixel in pixels.iter().filter(|pixel| self.degree(**pixel) != 2)
+        {
+            add_branches_from(pixel, &mut used);
+        }
+
+        // whatever is left are loops without any junctions
+        for &pixel in &pixels
+        {
+            add_branches_from(pixel, &mut used);
+        }
+
+        branches
+    }
+
+    pub fn pos(&self, pixel: usize) -> Pos
+    {
+        Pos::new((pixel % self.width) as f64, (pixel / self.width) as f64)
+    }
+}
+
+fn find_root(parents: &mut HashMap<usize, usize>, vertex: usize) -> usize
+{
+    let parent = *parents.entry(vertex).or_insert(vertex);
+    if parent == vertex
+    {
+        vertex
+    } else
+    {
+        let root = find_root(parents, parent);
+        parents.insert(vertex, root);
+
+        root
+    }
+}
+
+// pairs up odd vertices with jumps so every component needs as few strokes as possible
+fn add_jumps(graph: &PixelGraph, branches: &mut Vec<Branch>)
+{
+    let mut parents = HashMap::new();
+    let mut degrees: HashMap<usize, usize> = HashMap::new();
+
+    for branch in branches.iter()
+    {
+        let (a, b) = (find_root(&mut parents, branch.start), find_root(&mut parents, branch.end));
+        parents.insert(a, b);
+
+        *degrees.entry(branch.start).or_default() += 1;
+        *degrees.entry(branch.end).or_default() += 1;
+    }
+
+    let mut components: HashMap<usize, Vec<usize>> = HashMap::new();
+
+    let mut odd = degrees.into_iter().filter(|(_, degree)| degree % 2 == 1)
+        .map(|(vertex, _)| vertex)
+        .collect::<Vec<_>>();
+    odd.sort_unstable();
+
+    for vertex in odd
+    {
+        components.entry(find_root(&mut parents, vertex)).or_default().push(vertex);
+    }
+
+    let mut components = components.into_values().collect::<Vec<_>>();
+    components.sort_unstable();
+
+    for mut odd in components
+    {
+        // two odd vertices stay as the start and end of the path
+        while odd.len() > 2
+        {
+            let vertex = odd.pop().unwrap();
+
+            let closest = (0..odd.len()).min_by(|a, b|
+            {
+                let a = (graph.pos(odd[*a]) - graph.pos(vertex)).magnitude();
+                let b = (graph.pos(odd[*b]) - graph.pos(vertex)).magnitude();
+
+                a.total_cmp(&b)
+            }).unwrap();
+
+            let other = odd.swap_remove(closest);
+
+            branches.push(Branch{start: vertex, end: other, pixels: Vec::new(), jump: true});
+        }
+    }
+}
+
+// hierholzer's algorithm, returns the branches in order with the vertex each one starts from
+fn euler_trail(
+    start: usize,
+    branches: &[Branch],
+    incident: &HashMap<usize, Vec<usize>>,
+    used: &mut [bool]
+) -> Vec<(usize, usize)>
+{
+    let mut trail = Vec::new();
+    let mut stack: Vec<(usize, Option<(usize, usize)>)> = vec![(start, None)];
+
+    while let Some(&(vertex, arrived_by)) = stack.last()
+    {
+        let next = incident[&vertex].iter().copied().find(|branch| !used[*branch]);
+
+        if let Some(branch) = next
+        {
+            used[branch] = true;
+
+            let other = if branches[branch].start == vertex
+            {
+                branches[branch].end
+            } else
+            {
+                branches[branch].start
+            };
+
+            stack.push((other, Some((branch, vertex))));
+        } else
+        {
+            stack.pop();
+
+            if let Some(arrived_by) = arrived_by
+            {
+                trail.push(arrived_by);
+            }
+        }
+    }
+
+    trail.reverse();
+
+    trail
+}
+
+fn trail_strokes(branches: &[Branch], trail: &[(usize, usize)]) -> Vec<Vec<usize>>
+{
+    let mut strokes = Vec::new();
+    let mut current: Vec<usize> = Vec::new();
+
+    for &(index, from) in trail
+    {
+        let branch = &branches[index];
+
+        if branch.jump
+        {
+            strokes.push(current.split_off(0));
+            continue;
+        }
+
+        let mut pixels = branch.pixels.clone();
+        if branch.start != from
+        {
+            pixels.reverse();
+        }
+
+        let skip = if current.is_empty() { 0 } else { 1 };
+        current.extend(pixels.into_iter().skip(skip));
+    }
+
+    strokes.push(current);
+
+    strokes.into_iter().filter(|stroke| stroke.len() > 1).collect()
+}
+
+// every edge pixel gets drawn exactly once with the smallest amount of strokes
+pub fn strokes(image: &FloatImage, threshold: f64) -> Vec<Curve>
+{
+    let graph = PixelGraph::new(image, threshold);
+
+    let mut branches = graph.branches();
+    add_jumps(&graph, &mut branches);
+
+    let mut incident: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (index, branch) in branches.iter().enumerate()
+    {
+        incident.entry(branch.start).or_default().push(index);
+        incident.entry(branch.end).or_default().push(index);
+    }
+
+    // paths have to start from an odd vertex
+    let mut starts = incident.iter()
+        .map(|(vertex, incident)| (incident.len() % 2 == 0, *vertex))
+        .collect::<Vec<_>>();
+    starts.sort_unstable();
+
+    let mut used = vec![false; branches.len()];
+
+    let mut curves = Vec::new();
+    for (_, start) in starts
+    {
+        let trail = euler_trail(start, &branches, &incident, &mut used);
+
+        for stroke in trail_strokes(&branches, &trail)
+        {
+            let closed = stroke.len() > 2 && stroke.first() == stroke.last();
+
+            let mut points = stroke.into_iter().map(|pixel|
+            {
+                let pos = graph.pos(pixel);
+
+                image.normalized(pos.x, pos.y)
+            }).collect::<Vec<_>>();
+
+            curves.push(if closed
+            {
+                points.pop();
+
+                Curve::new_closed(points)
+            } else
+            {
+                Curve::new(points)
+            });
+        }
+    }
+
+    curves
+}
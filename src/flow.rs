@@ -0,0 +1,168 @@
+use std::f64::consts::FRAC_PI_2;
+
+use crate::{
+    FloatImage,
+    blur,
+    stipple::PointGrid,
+    contour::{Curve, Pos}
+};
+
+
+// areas lighter than this dont get any lines
+const MINIMUM_DARKNESS: f64 = 0.1;
+
+// how close (relative to the separation) a line can get to another before stopping
+const TEST_RATIO: f64 = 0.5;
+
+struct FlowField
+{
+    // doubled angle vectors so opposite edge directions dont cancel out when blurred
+    cos: FloatImage,
+    sin: FloatImage
+}
+
+impl FlowField
+{
+    pub fn new(directions: &FloatImage, gradient: &FloatImage) -> Self
+    {
+        let doubled = |f: fn(f64) -> f64|
+        {
+            let data = directions.data().iter().zip(gradient.data()).map(|(direction, magnitude)|
+            {
+                f(direction * 2.0) * magnitude
+            }).collect();
+
+            blur(&blur(&FloatImage::new(data, directions.width(), directions.height())))
+        };
+
+        Self{cos: doubled(f64::cos), sin: doubled(f64::sin)}
+    }
+
+    // unit vector along the edges (perpendicular to the gradient)
+    pub fn direction(&self, pos: Pos) -> Pos
+    {
+        let x = pos.x.clamp(0.0, (self.cos.width() - 1) as f64);
+        let y = pos.y.clamp(0.0, (self.cos.height() - 1) as f64);
+
+        let angle = self.sin.fget(x, y).atan2(self.cos.fget(x, y)) / 2.0 + FRAC_PI_2;
+
+        Pos::new(angle.cos(), angle.sin())
+    }
+}
+
+fn separation(image: &FloatImage, pos: Pos, spacing: f64) -> f64
+{
+    spacing * (1.0 + 3.0 * (1.0 - image.darkness(pos.x, pos.y)))
+}
+
+fn integrate(
+    image: &FloatImage,
+    field: &FlowField,
+    grid: &PointGrid,
+    seed: Pos,
+    backwards: bool,
+    spacing: f64
+) -> Vec<Pos>
+{
+    let (width, height) = (image.width() as f64, image.height() as f64);
+    let max_steps = 2 * (image.width() + image.height());
+
+    let mut points = Vec::new();
+
+    let mut current = seed;
+    let mut previous = field.direction(seed);
+    if backwards
+    {
+        previous = -previous;
+    }
+
+    for _ in 0..max_steps
+    {
+        let mut direction = field.direction(current);
+        if direction.dot(previous) < 0.0
+        {
+            direction = -direction;
+        }
+
+        let next = current + direction;
+
+        let inside = (0.0..width).contains(&next.x) && (0.0..height).contains(&next.y);
+        if !inside || image.darkness(next.x, next.y) < MINIMUM_DARKNESS
+        {
+            break;
+        }
+
+        if grid.any_within(next, separation(image, next, spacing) * TEST_RATIO)
+        {
+            break;
+        }
+
+        points.push(next);
+
+        current = next;
+        previous = direction;
+    }
+
+    points
+}
+
+// evenly spaced streamlines following the edges, denser in darker areas
+pub fn flow_lines(
+    image: &FloatImage,
+    directions: &FloatImage,
+    gradient: &FloatImage,
+    spacing: f64
+) -> Vec<Curve>
+{
+    if spacing <= 0.0
+    {
+        panic!("invalid flow spacing");
+    }
+
+    let field = FlowField::new(directions, gradient);
+
+    let (width, height) = (image.width() as f64, image.height() as f64);
+    let mut grid = PointGrid::new(width, height, spacing);
+
+    let mut curves = Vec::new();
+
+    let seed_step = spacing / 2.0;
+
+    let mut y = seed_step / 2.0;
+    while y < height
+    {
+        let mut x = seed_step / 2.0;
+        while x < width
+        {
+            let seed = Pos::new(x, y);
+            x += seed_step;
+
+            if image.darkness(seed.x, seed.y) < MINIMUM_DARKNESS
+                || grid.any_within(seed, separation(image, seed, spacing))
+            {
+                continue;
+            }
+
+            let mut points = integrate(image, &field, &grid, seed, true, spacing);
+            points.reverse();
+            points.push(seed);
+            points.extend(integrate(image, &field, &grid, seed, false, spacing));
+
+            if points.len() < spacing as usize
+            {
+                continue;
+            }
+
+            points.iter().for_each(|pos| grid.insert(*pos));
+
+            curves.push(Curve::new(points.into_iter().map(|pos|
+            {
+                image.normalized(pos.x, pos.y)
+            }).collect()));
+        }
+
+        y += seed_step;
+    }
+
+    curves
+}
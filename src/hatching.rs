@@ -0,0 +1,109 @@
+use std::f64::consts::FRAC_PI_4;
+
+use crate::{
+    FloatImage,
+    contour::{Curve, Pos}
+};
+
+
+struct HatchLayer
+{
+    angle: f64,
+    phase: f64,
+    threshold: f64
+}
+
+// each layer only draws where the image is darker than its threshold
+// so darker areas get denser lines and crosshatching
+const LAYERS: [HatchLayer; 4] = [
+    HatchLayer{angle: FRAC_PI_4, phase: 0.0, threshold: 0.2},
+    HatchLayer{angle: -FRAC_PI_4, phase: 0.0, threshold: 0.45},
+    HatchLayer{angle: FRAC_PI_4, phase: 0.5, threshold: 0.65},
+    HatchLayer{angle: -FRAC_PI_4, phase: 0.5, threshold: 0.85}
+];
+
+pub fn hatch(image: &FloatImage, spacing: f64) -> Vec<Curve>
+{
+    if spacing <= 0.0
+    {
+        panic!("invalid hatch spacing");
+    }
+
+    LAYERS.iter().flat_map(|layer| hatch_layer(image, layer, spacing)).collect()
+}
+
+fn hatch_layer(image: &FloatImage, layer: &HatchLayer, spacing: f64) -> Vec<Curve>
+{
+    let (max_x, max_y) = ((image.width() - 1) as f64, (image.height() - 1) as f64);
+
+    let direction = Pos::new(layer.angle.cos(), layer.angle.sin());
+    let normal = Pos::new(-direction.y, direction.x);
+
+    let corners = [(0.0, 0.0), (max_x, 0.0), (0.0, max_y), (max_x, max_y)];
+    let range = |axis: Pos|
+    {
+        corners.iter().map(|(x, y)| x * axis.x + y * axis.y)
+            .fold((f64::MAX, f64::MIN), |(low, high), v| (low.min(v), high.max(v)))
+    };
+
+    let (normal_low, normal_high) = range(normal);
+    let (along_low, along_high) = range(direction);
+
+    let mut curves = Vec::new();
+
+    let mut offset = normal_low + layer.phase * spacing;
+    let mut line_index = 0;
+    while offset <= normal_high
+    {
+        let mut runs = Vec::new();
+        let mut run: Vec<Pos> = Vec::new();
+
+        let mut along = along_low;
+        while along <= along_high
+        {
+            let x = normal.x * offset + direction.x * along;
+            let y = normal.y * offset + direction.y * along;
+
+            let inside = (0.0..=max_x).contains(&x) && (0.0..=max_y).contains(&y);
+            if inside && image.darkness(x, y) > layer.threshold
+            {
+                run.push(image.normalized(x, y));
+            } else if !run.is_empty()
+            {
+                runs.push(run.split_off(0));
+            }
+
+            along += 1.0;
+        }
+
+        if !run.is_empty()
+        {
+            runs.push(run);
+        }
+
+        let runs = runs.into_iter().filter(|run| run.len() > 1).map(|run|
+        {
+            Curve::new(vec![run[0], run[run.len() - 1]])
+        });
+
+        // serpentine order, every other line goes backwards
+        if line_index % 2 == 0
+        {
+            curves.extend(runs);
+        } else
+        {
+            let mut runs = runs.collect::<Vec<_>>();
+            runs.reverse();
+
+            curves.extend(runs.into_iter().map(|curve|
+            {
+                Curve::new(vec![curve[1], curve[0]])
+            }));
+        }
+
+        offset += spacing;
+        line_index += 1;
+    }
+
+    curves
+}
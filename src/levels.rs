@@ -0,0 +1,38 @@
+use crate::{
+    FloatImage,
+    contour::{self, Curve, Tracer}
+};
+
+
+pub fn even_thresholds(amount: usize) -> Vec<f64>
+{
+    (1..=amount).map(|index| index as f64 / (amount + 1) as f64).collect()
+}
+
+pub fn parse_thresholds(text: &str) -> Result<Vec<f64>, String>
+{
+    let mut thresholds = text.split(',').map(|value|
+    {
+        let value = value.trim();
+
+        value.parse::<f64>().map_err(|_| format!("invalid threshold: {value}"))
+    }).collect::<Result<Vec<_>, _>>()?;
+
+    thresholds.sort_by(|a, b| a.total_cmp(b));
+
+    Ok(thresholds)
+}
+
+// traces the contours of every brightness level separately, one list of curves per threshold
+// thresholds are expected to be sorted from darkest to brightest
+pub fn level_contours(
+    image: &FloatImage,
+    thresholds: &[f64],
+    tracer: Tracer
+) -> Vec<Vec<Curve>>
+{
+    thresholds.iter().map(|threshold|
+    {
+        contour::trace(image, *threshold, tracer)
+    }).collect()
+}
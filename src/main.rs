@@ -1,7 +1,10 @@
+#![allow(clippy::suspicious_else_formatting)]
+
 use std::{
     io,
     thread,
     process,
+    str::FromStr,
     sync::{
         Arc,
         atomic::{AtomicBool, Ordering}
@@ -9,23 +12,41 @@ use std::{
     time::Duration
 };
 
-use argparse::{ArgumentParser, Store, StoreTrue};
+use argparse::{ArgumentParser, Store, StoreOption, StoreTrue};
 
 use image::GrayImage;
 
 use contour::{
-    Pos
+    Curve,
+    Pos,
+    Tracer
 };
 
 use drawer::LineDrawer;
 
+use ordering::Order;
+
+use transform::{Transform, Placement, Fit, Anchor};
+
+use contour::{simplify::Algorithm, smooth::Smoothing, bezier::BezierPath};
+
 use device_query::{
     keymap::Keycode,
     device_state::DeviceState
 };
 
 mod contour;
+mod edge_graph;
 mod drawer;
+mod hatching;
+mod stipple;
+mod spiral;
+mod flow;
+mod levels;
+mod ordering;
+mod tsp;
+mod svg;
+mod transform;
 
 
 #[derive(Debug, Clone)]
@@ -79,6 +100,27 @@ impl FloatImage
         (n_low as usize, n_high as usize, a)
     }
 
+    // 0 for white, 1 for black, clamped to the image edges
+    pub fn darkness(&self, x: f64, y: f64) -> f64
+    {
+        let x = x.clamp(0.0, (self.width - 1) as f64);
+        let y = y.clamp(0.0, (self.height - 1) as f64);
+
+        1.0 - self.fget(x, y)
+    }
+
+    // size of a pixel once the longer side is scaled to 1
+    pub fn pixel_size(&self) -> f64
+    {
+        1.0 / self.width.max(self.height) as f64
+    }
+
+    // same scale on both axes so distances mean the same thing in every direction
+    pub fn normalized(&self, x: f64, y: f64) -> Pos
+    {
+        Pos::new(x * self.pixel_size(), y * self.pixel_size())
+    }
+
     pub fn data(&self) -> &[f64]
     {
         &self.data
@@ -146,7 +188,7 @@ fn filter_image<const S: usize>(image: &FloatImage, kernel: &[f64], average: boo
                         continue;
                     }
 
-                    let kernel_value = kernel[(k_y * S + k_x) as usize];
+                    let kernel_value = kernel[k_y * S + k_x];
                     scale += kernel_value;
 
                     let pixel = image.get(x as usize, y as usize).unwrap();
@@ -205,16 +247,130 @@ fn edge_thinning(gradient: &FloatImage, directions: &FloatImage) -> FloatImage
     thinned
 }
 
+fn blur(image: &FloatImage) -> FloatImage
+{
+    filter_image::<5>(image,
+        &[2.0, 4.0, 5.0, 4.0, 2.0,
+        4.0, 9.0, 12.0, 9.0, 4.0,
+        5.0, 12.0, 15.0, 12.0, 5.0,
+        4.0, 9.0, 12.0, 9.0, 4.0,
+        2.0, 4.0, 5.0, 4.0, 2.0
+        ], true)
+}
+
+// returns the directions and magnitudes of the gradient
+fn image_gradients(blurred_image: &FloatImage) -> (FloatImage, FloatImage)
+{
+    let image_horiz = filter_image::<5>(blurred_image,
+        &[1.0, 0.0, 0.0, 0.0, -1.0,
+        2.0, 0.0, 0.0, 0.0, -2.0,
+        3.0, 0.0, 0.0, 0.0, -3.0,
+        2.0, 0.0, 0.0, 0.0, -2.0,
+        1.0, 0.0, 0.0, 0.0, -1.0], false);
+
+    let image_vert = filter_image::<5>(blurred_image,
+        &[1.0, 2.0, 3.0, 2.0, 1.0,
+        0.0, 0.0, 0.0, 0.0, 0.0,
+        0.0, 0.0, 0.0, 0.0, 0.0,
+        0.0, 0.0, 0.0, 0.0, 0.0,
+        -1.0, -2.0, -3.0, -2.0, -1.0], false);
+
+    combine_edges(&image_horiz, &image_vert)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DrawMode
+{
+    Edges,
+    Hatch,
+    Stipple,
+    Tsp,
+    Spiral,
+    Flow,
+    Levels
+}
+
+impl FromStr for DrawMode
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        match s.to_lowercase().as_str()
+        {
+            "edges" => Ok(Self::Edges),
+            "hatch" => Ok(Self::Hatch),
+            "stipple" => Ok(Self::Stipple),
+            "tsp" => Ok(Self::Tsp),
+            "spiral" => Ok(Self::Spiral),
+            "flow" => Ok(Self::Flow),
+            "levels" => Ok(Self::Levels),
+            _ => Err(format!("unknown mode: {s}"))
+        }
+    }
+}
+
+// drops points that would move the mouse to the pixel its already on
+fn dedup_screen_pixels(curve: Curve, screen_pixel: impl Fn(Pos) -> (usize, usize)) -> Curve
+{
+    let closed = curve.is_closed();
+
+    let mut last_pixel = None;
+    let points = curve.into_iter().filter(|pos|
+    {
+        let pixel = Some(screen_pixel(*pos));
+        let moved = pixel != last_pixel;
+
+        last_pixel = pixel;
+
+        moved
+    }).collect();
+
+    Curve::from_points(points, closed)
+}
+
 fn main()
 {
     let mut path = String::new();
     let mut epsilon = 0.01;
+    let mut simplify_algorithm = Algorithm::DouglasPeucker;
+    let mut screen_epsilon: Option<f64> = None;
+    let mut smoothing: Option<Smoothing> = None;
+    let mut smooth_step = 0.004;
+    let mut corner_angle = 60.0;
+    let mut pin_corners = false;
+    let mut split_corners = false;
+    let mut corner_support = 4.0;
+    let mut recognise_primitives = false;
+    let mut primitive_tolerance = 1.5;
+    let mut bezier = false;
+    let mut svg_path: Option<String> = None;
+    let mut resample_spacing: Option<f64> = None;
+    let mut max_segment: Option<f64> = None;
+    let mut rotation = 0.0;
+    let mut flip_x = false;
+    let mut flip_y = false;
+    let mut scale = 1.0;
+    let mut anchor = Anchor::default();
+    let mut margin = 0.0;
+    let mut fit = Fit::Contain;
     let mut minimum_length = 0.0;
     let mut threshold = 0.5;
     let mut delay = 0.05;
     let mut verbose = false;
     let mut save_edges = false;
     let mut show_area = false;
+    let mut mode = DrawMode::Edges;
+    let mut tracer = Tracer::Suzuki;
+    let mut max_depth: Option<usize> = None;
+    let mut order: Option<Order> = None;
+    let mut join_tolerance: Option<f64> = None;
+    let mut join_angle: Option<f64> = None;
+    let mut spacing = 6.0;
+    let mut amplitude = 2.5;
+    let mut time_budget = 300.0;
+    let mut levels_amount = 4;
+    let mut thresholds = String::new();
 
     let mut window_name = "Transformice".to_owned();
 
@@ -222,14 +378,25 @@ fn main()
     let (mut max_width, mut max_height) = (0.634, 0.575);
 
     // wouldve been easier to use my own, better, args parser :/
-    let epsilon_d = format!("epsilon for line simplification (default {epsilon})");
-    let length_d = format!("minimum length for a line (default {minimum_length})");
+    let epsilon_d = format!("epsilon for line simplification as a fraction of the longer image side (default {epsilon})");
+    let smooth_step_d = format!("segment length the smoothing aims for as a fraction of the longer image side (default {smooth_step})");
+    let corner_angle_d = format!("turns sharper than this in degrees are kept as corners when smoothing or detecting corners (default {corner_angle})");
+    let corner_support_d = format!("distance in pixels on each side of a point used to measure how sharply it turns (default {corner_support})");
+    let primitive_tolerance_d = format!("how far in pixels a curve can stray from a recognised shape (default {primitive_tolerance})");
+    let length_d = format!("minimum length for a line as a fraction of the longer image side (default {minimum_length})");
     let threshold_d = format!("threshold for edge detection (default {threshold})");
     let delay_d = format!("delay between each action in seconds (default {delay})");
     let canvas_x_d = format!("canvas x starting point (default {canvas_x})");
     let canvas_y_d = format!("canvas y starting point (default {canvas_y})");
     let max_width_d = format!("canvas width (default {max_width})");
     let max_height_d = format!("canvas height (default {max_height})");
+    let rotation_d = format!("rotate the drawing clockwise by this many degrees (default {rotation})");
+    let scale_d = format!("size of the drawing relative to how it fits the canvas (default {scale})");
+    let margin_d = format!("empty space on every side as a fraction of the shorter canvas side, from 0 up to 0.5 (default {margin})");
+    let spacing_d = format!("spacing between hatch lines, spiral turns or flow lines in pixels (default {spacing})");
+    let amplitude_d = format!("maximum wobble of the spiral in pixels (default {amplitude})");
+    let levels_amount_d = format!("amount of evenly spaced brightness levels (default {levels_amount})");
+    let time_budget_d = format!("maximum drawing time in seconds for dot based modes (default {time_budget})");
 
     {
         let mut parser = ArgumentParser::new();
@@ -240,6 +407,76 @@ fn main()
                 &epsilon_d
             );
 
+        parser.refer(&mut simplify_algorithm)
+            .add_option(&["--simplify"], Store,
+                "line simplification: dp (douglas peucker), vw (visvalingam whyatt) or rw (reumann witkam) (default dp)"
+            );
+
+        parser.refer(&mut screen_epsilon)
+            .add_option(&["--screen-epsilon"], StoreOption,
+                "epsilon for line simplification in screen pixels of the canvas, overrides --epsilon and drops points landing on the same pixel"
+            );
+
+        parser.refer(&mut smoothing)
+            .add_option(&["--smooth"], StoreOption,
+                "smooth curves after simplification: chaikin or catmull-rom"
+            );
+
+        parser.refer(&mut smooth_step)
+            .add_option(&["--smooth-step"], Store,
+                &smooth_step_d
+            );
+
+        parser.refer(&mut corner_angle)
+            .add_option(&["--corner-angle"], Store,
+                &corner_angle_d
+            );
+
+        parser.refer(&mut pin_corners)
+            .add_option(&["--corners"], StoreTrue,
+                "detect corners on the traced curves and keep them through simplification and smoothing"
+            );
+
+        parser.refer(&mut split_corners)
+            .add_option(&["--split-corners"], StoreTrue,
+                "like --corners but also draws the pieces between corners as separate strokes"
+            );
+
+        parser.refer(&mut corner_support)
+            .add_option(&["--corner-support"], Store,
+                &corner_support_d
+            );
+
+        parser.refer(&mut recognise_primitives)
+            .add_option(&["--primitives"], StoreTrue,
+                "replace curves that are straight lines, circles or ellipses with exact ones"
+            );
+
+        parser.refer(&mut primitive_tolerance)
+            .add_option(&["--primitive-tolerance"], Store,
+                &primitive_tolerance_d
+            );
+
+        parser.refer(&mut bezier)
+            .add_option(&["--bezier"], StoreTrue,
+                "fit potrace style bezier curves to traced outlines instead of simplifying and smoothing them"
+            );
+
+        parser.refer(&mut svg_path)
+            .add_option(&["--svg"], StoreOption,
+                "save the curves (or fitted beziers) to this svg file before drawing"
+            );
+
+        parser.refer(&mut resample_spacing)
+            .add_option(&["--resample"], StoreOption,
+                "respace the points of every curve evenly this far apart, as a fraction of the longer image side"
+            );
+
+        parser.refer(&mut max_segment)
+            .add_option(&["--max-segment"], StoreOption,
+                "split lines longer than this into shorter ones, as a fraction of the longer image side"
+            );
+
         parser.refer(&mut minimum_length)
             .add_option(&["-l", "--length"], Store,
                 &length_d
@@ -295,6 +532,96 @@ fn main()
             &max_height_d
         );
 
+        parser.refer(&mut mode)
+            .add_option(&["-m", "--mode"], Store,
+            "drawing mode: edges, hatch, stipple, tsp, spiral, flow or levels (default edges)"
+        );
+
+        parser.refer(&mut tracer)
+            .add_option(&["--tracer"], Store,
+            "contour tracer: suzuki, marching (sub pixel isolines) or graph (edges drawn once, edges mode only) (default suzuki)"
+        );
+
+        parser.refer(&mut max_depth)
+            .add_option(&["--depth"], StoreOption,
+            "skip contours nested deeper than this (0 keeps only outer borders, suzuki tracer only)"
+        );
+
+        parser.refer(&mut order)
+            .add_option(&["--order"], StoreOption,
+            "curve order: optimize (shortest pen travel) or keep (as generated) (default keep for levels mode and the suzuki tracer in edges mode, optimize otherwise)"
+        );
+
+        parser.refer(&mut join_tolerance)
+            .add_option(&["-j", "--join"], StoreOption,
+            "join curves with ends closer than this into one stroke, as a fraction of the longer image side"
+        );
+
+        parser.refer(&mut join_angle)
+            .add_option(&["--join-angle"], StoreOption,
+            "maximum turn in degrees allowed when joining curves"
+        );
+
+        parser.refer(&mut rotation)
+            .add_option(&["--rotate"], Store,
+            &rotation_d
+        );
+
+        parser.refer(&mut flip_x)
+            .add_option(&["--flip-x"], StoreTrue,
+            "mirror the drawing horizontally"
+        );
+
+        parser.refer(&mut flip_y)
+            .add_option(&["--flip-y"], StoreTrue,
+            "mirror the drawing vertically"
+        );
+
+        parser.refer(&mut scale)
+            .add_option(&["--scale"], Store,
+            &scale_d
+        );
+
+        parser.refer(&mut anchor)
+            .add_option(&["--anchor"], Store,
+            "where the drawing sits in the canvas: center, top-left, top, top-right, left, right, bottom-left, bottom or bottom-right (default center)"
+        );
+
+        parser.refer(&mut margin)
+            .add_option(&["--margin"], Store,
+            &margin_d
+        );
+
+        parser.refer(&mut fit)
+            .add_option(&["--fit"], Store,
+            "contain fits the whole drawing, cover fills the canvas and crops, stretch fills it without keeping the aspect ratio (default contain)"
+        );
+
+        parser.refer(&mut spacing)
+            .add_option(&["--spacing"], Store,
+            &spacing_d
+        );
+
+        parser.refer(&mut amplitude)
+            .add_option(&["--amplitude"], Store,
+            &amplitude_d
+        );
+
+        parser.refer(&mut time_budget)
+            .add_option(&["-T", "--time"], Store,
+            &time_budget_d
+        );
+
+        parser.refer(&mut levels_amount)
+            .add_option(&["--levels"], Store,
+            &levels_amount_d
+        );
+
+        parser.refer(&mut thresholds)
+            .add_option(&["--thresholds"], Store,
+            "comma separated brightness levels, overrides --levels (for example 0.2,0.5,0.8)"
+        );
+
         parser.refer(&mut path)
             .add_option(&["-i", "--input"], Store, "path to the image file")
             .add_argument("image_path", Store, "path to the image file")
@@ -303,6 +630,27 @@ fn main()
         parser.parse_args_or_exit();
     }
 
+    if resample_spacing.into_iter().chain(max_segment).any(|length| length <= 0.0)
+    {
+        eprintln!("the resample spacing and max segment length have to be above 0");
+        process::exit(2);
+    }
+
+    let placement = Placement{
+        rotation: f64::to_radians(rotation),
+        flip_x,
+        flip_y,
+        scale,
+        anchor,
+        margin,
+        fit
+    };
+
+    placement.check().unwrap_or_else(|err|
+    {
+        eprintln!("{err}");
+        process::exit(2);
+    });
 
     let image = image::open(path.clone()).unwrap_or_else(|err|
     {
@@ -322,84 +670,350 @@ fn main()
         image_height
     );
 
-    let blurred_image = filter_image::<5>(&float_image,
-        &[2.0, 4.0, 5.0, 4.0, 2.0,
-        4.0, 9.0, 12.0, 9.0, 4.0,
-        5.0, 12.0, 15.0, 12.0, 5.0,
-        4.0, 9.0, 12.0, 9.0, 4.0,
-        2.0, 4.0, 5.0, 4.0, 2.0
-        ], true);
+    let create_line_drawer = ||
+    {
+        LineDrawer::new(&window_name, delay, verbose).unwrap_or_else(||
+        {
+            eprintln!("window not found, is it open and visible?");
+            process::exit(3);
+        })
+    };
 
-    let image_horiz = filter_image::<5>(&blurred_image,
-        &[1.0, 0.0, 0.0, 0.0, -1.0,
-        2.0, 0.0, 0.0, 0.0, -2.0,
-        3.0, 0.0, 0.0, 0.0, -3.0,
-        2.0, 0.0, 0.0, 0.0, -2.0,
-        1.0, 0.0, 0.0, 0.0, -1.0], false);
+    let mut line_drawer = create_line_drawer();
 
-    let image_vert = filter_image::<5>(&blurred_image,
-        &[1.0, 2.0, 3.0, 2.0, 1.0,
-        0.0, 0.0, 0.0, 0.0, 0.0,
-        0.0, 0.0, 0.0, 0.0, 0.0,
-        0.0, 0.0, 0.0, 0.0, 0.0,
-        -1.0, -2.0, -3.0, -2.0, -1.0], false);
+    let width = image_width as f64;
+    let height = image_height as f64;
+
+    // curves are scaled by the longer side so the shorter one doesnt reach all the way
+    let (width, height) = (width / width.max(height), height / width.max(height));
+
+    // the canvas in screen pixels, so fitting keeps the real aspect ratio
+    let (window_width, window_height) = line_drawer.size();
+    let canvas = Pos::new(max_width * window_width, max_height * window_height);
 
-    let (directions, gradient) = combine_edges(&image_horiz, &image_vert);
-    let thinned = edge_thinning(&gradient, &directions);
+    let to_canvas = placement.transform(Pos::new(width, height), canvas);
+    let to_window = to_canvas
+        .then(Transform::scale(1.0 / window_width, 1.0 / window_height))
+        .then(Transform::translate(Pos::new(canvas_x, canvas_y)));
 
-    if save_edges
+    if verbose
     {
-        thinned.save("edges.png");
+        eprintln!("canvas: {:.0}x{:.0} pixels", canvas.x, canvas.y);
+        eprintln!("placed at {:.1} screen pixels per unit", to_canvas.max_scale());
     }
 
-    let mut curves = contour::contours(&thinned, threshold, epsilon);
-    curves.sort_by(|x, y|
-    {
-        y.curve_length().total_cmp(&x.curve_length())
-    });
+    let map_point = |pos: Pos| to_window.apply(pos);
 
-    if let Some(index) = curves.iter().map(|x| x.curve_length()).position(|x| x < minimum_length)
+    // how many screen pixels one unit of curve space covers, the finer axis wins
+    let screen_scale = to_canvas.max_scale();
+
+    if let Some(pixels) = screen_epsilon
     {
-        curves.truncate(index);
+        epsilon = pixels / screen_scale;
+
+        if verbose
+        {
+            eprintln!("epsilon of {pixels} screen pixels: {epsilon:.5}");
+        }
     }
 
-    let time_to_draw: f64 = curves.iter().map(|curve|
-    {
-        curve.len() as f64 * (delay / 2.0) + delay * 2.0
-    }).sum();
+    let blurred_image = blur(&float_image);
 
-    let create_line_drawer = ||
+    let mut curves = match mode
     {
-        LineDrawer::new(&window_name, delay, verbose).unwrap_or_else(||
+        DrawMode::Edges =>
         {
-            eprintln!("window not found, is it open and visible?");
-            process::exit(3);
-        })
+            let (directions, gradient) = image_gradients(&blurred_image);
+            let thinned = edge_thinning(&gradient, &directions);
+
+            if save_edges
+            {
+                thinned.save("edges.png");
+            }
+
+            if tracer == Tracer::Suzuki
+            {
+                contour::contour_tree(&thinned, threshold).outer_first(max_depth)
+            } else
+            {
+                let mut curves = contour::trace(&thinned, threshold, tracer);
+                curves.sort_by(|x, y|
+                {
+                    y.curve_length().total_cmp(&x.curve_length())
+                });
+
+                curves
+            }
+        },
+        DrawMode::Hatch => hatching::hatch(&blurred_image, spacing),
+        DrawMode::Stipple | DrawMode::Tsp if delay <= 0.0 =>
+        {
+            eprintln!("the amount of dots comes from the delay, it has to be above 0");
+            process::exit(2);
+        },
+        DrawMode::Stipple =>
+        {
+            // a dot is a move, a click and a release
+            let max_dots = (time_budget / (delay * 2.5)) as usize;
+
+            stipple::stipple(&blurred_image, max_dots)
+        },
+        DrawMode::Tsp =>
+        {
+            // one long stroke only pays for the moves between points
+            let max_points = (time_budget / (delay / 2.0)) as usize;
+
+            let points = stipple::stipple_points(&blurred_image, max_points);
+
+            let points = tsp::tour(points).into_iter().map(|pos|
+            {
+                blurred_image.normalized(pos.x, pos.y)
+            }).collect::<Vec<_>>();
+
+            if points.is_empty()
+            {
+                Vec::new()
+            } else
+            {
+                vec![Curve::new(points)]
+            }
+        },
+        DrawMode::Spiral => vec![spiral::spiral(&blurred_image, spacing, amplitude)],
+        DrawMode::Flow =>
+        {
+            let (directions, gradient) = image_gradients(&blurred_image);
+
+            flow::flow_lines(&blurred_image, &directions, &gradient, spacing)
+        },
+        DrawMode::Levels =>
+        {
+            if tracer == Tracer::Graph
+            {
+                eprintln!("the graph tracer only works in edges mode");
+                process::exit(2);
+            }
+
+            let thresholds = if thresholds.is_empty()
+            {
+                levels::even_thresholds(levels_amount)
+            } else
+            {
+                levels::parse_thresholds(&thresholds).unwrap_or_else(|err|
+                {
+                    eprintln!("{err}");
+                    process::exit(2);
+                })
+            };
+
+            let level_curves = levels::level_contours(&blurred_image, &thresholds, tracer);
+
+            if verbose
+            {
+                for (level, (threshold, curves)) in thresholds.iter().zip(&level_curves).enumerate()
+                {
+                    eprintln!("level {level} (threshold {threshold:.3}): {} curves", curves.len());
+                }
+            }
+
+            // darkest levels first, longest curves first inside each level
+            level_curves.into_iter().flat_map(|mut curves|
+            {
+                curves.sort_by(|x, y|
+                {
+                    y.curve_length().total_cmp(&x.curve_length())
+                });
+
+                curves
+            }).collect()
+        }
     };
 
-    let mut line_drawer = create_line_drawer();
+    // spirals and tsp art are made of tiny wiggles that simplification would flatten
+    let simplified = matches!(mode, DrawMode::Edges | DrawMode::Levels | DrawMode::Flow);
+    let fit_beziers = bezier && simplified;
 
-    let width = image_width as f64;
-    let height = image_height as f64;
+    // the drawer waits the same time after every point, evenly spaced points keep its speed steady
+    let space_out = |mut curve: Curve|
+    {
+        if let Some(spacing) = resample_spacing
+        {
+            curve = curve.resample(spacing);
+        }
+
+        if let Some(max_length) = max_segment
+        {
+            curve = curve.subdivide(max_length);
+        }
 
-    let (width, height) = if width > height
+        curve
+    };
+
+    let refine = |mut curves: Vec<Curve>|
     {
-        (1.0, height / width)
-    } else
+        // bezier fitting needs the raw traced points, it simplifies and smooths them itself
+        if fit_beziers
+        {
+            return curves;
+        }
+
+        if simplified
+        {
+            curves = contour::simplify::simplify_borders(curves, epsilon, simplify_algorithm);
+        }
+
+        if let Some(smoothing) = smoothing
+        {
+            curves = contour::smooth::smooth_curves(curves, smoothing, smooth_step, f64::to_radians(corner_angle));
+        }
+
+        // pieces between pinned corners get spaced out on their own so the corners stay sample points
+        curves.into_iter().map(space_out).collect()
+    };
+
+    let support = corner_support * float_image.pixel_size();
+    let refine_curve = |curve: Curve|
     {
-        (width / height, 1.0)
+        if pin_corners || split_corners
+        {
+            // pieces between corners get refined on their own so the corners stay where they are
+            let pieces = contour::corners::split_at_corners(curve, support, f64::to_radians(corner_angle))
+                .map(refine);
+
+            if split_corners
+            {
+                pieces.into_strokes()
+            } else
+            {
+                vec![pieces.into_curve()]
+            }
+        } else
+        {
+            refine(vec![curve])
+        }
     };
 
-    let (offset_x, offset_y) = ((1.0 - width) / 2.0, (1.0 - height) / 2.0);
+    let primitive_tolerance = primitive_tolerance * float_image.pixel_size();
+    curves = curves.into_iter().flat_map(|curve|
+    {
+        let primitive = recognise_primitives.then(||
+        {
+            contour::primitives::recognise(&curve, primitive_tolerance)
+        }).flatten();
+
+        // exact shapes are already as clean as they get
+        if let Some(primitive) = primitive
+        {
+            if verbose
+            {
+                eprintln!("found {primitive}");
+            }
+
+            vec![space_out(primitive.to_curve(primitive_tolerance))]
+        } else
+        {
+            refine_curve(curve)
+        }
+    }).collect();
+
+    if fit_beziers || svg_path.is_some()
+    {
+        let paths = curves.iter().map(|curve|
+        {
+            if fit_beziers
+            {
+                contour::potrace::fit_path(curve, float_image.pixel_size())
+            } else
+            {
+                BezierPath::from_curve(curve)
+            }
+        }).collect::<Vec<_>>();
+
+        if let Some(svg_path) = &svg_path
+        {
+            svg::save(svg_path, &paths, image_width, image_height).unwrap_or_else(|err|
+            {
+                eprintln!("couldnt save the svg at: {svg_path}");
+                eprintln!("{err}");
+                process::exit(4);
+            });
+        }
+
+        if fit_beziers
+        {
+            // a quarter pixel is well below what the mouse can tell apart
+            let tolerance = float_image.pixel_size() * 0.25;
+
+            curves = paths.iter().map(|path| space_out(path.flatten(tolerance))).collect();
+        }
+    }
+
+    if screen_epsilon.is_some()
+    {
+        curves = curves.into_iter().map(|curve|
+        {
+            dedup_screen_pixels(curve, |pos| line_drawer.screen_pixel(map_point(pos)))
+        }).collect();
+    }
+
+    // anything past the canvas edges (from cover, scaling up or rotating) gets cut off
+    let (canvas_low, canvas_high) = placement.area(canvas);
+    let from_canvas = to_canvas.inverse().unwrap();
+
+    curves = curves.into_iter().flat_map(|curve|
+    {
+        contour::clip::clip_curve(curve.map(|pos| to_canvas.apply(pos)), canvas_low, canvas_high)
+    }).map(|curve| curve.map(|pos| from_canvas.apply(pos))).collect();
+
+    if let Some(tolerance) = join_tolerance
+    {
+        let amount = curves.len();
+
+        curves = contour::join::join_curves(curves, tolerance, join_angle.map(f64::to_radians));
+
+        if verbose
+        {
+            eprintln!("joined {amount} curves into {}", curves.len());
+        }
+    }
+
+    // stipple dots have no length but still have to be drawn, stray traced pixels dont
+    let is_dot = |curve: &Curve| mode == DrawMode::Stipple && curve.len() == 1;
+
+    let amount = curves.len();
+    curves.retain(|curve| is_dot(curve) || curve.curve_length() >= minimum_length);
 
     if verbose
     {
-        eprintln!("offset_x: {offset_x:.3}, offset_y: {offset_y:.3}");
-        eprintln!("width: {width:.3}, height: {height:.3}");
+        eprintln!("dropped {} curves shorter than {minimum_length}", amount - curves.len());
+    }
+
+    // darkest levels first and outer borders first are already a drawing order
+    let ordered = mode == DrawMode::Levels || (mode == DrawMode::Edges && tracer == Tracer::Suzuki);
+    let order = order.unwrap_or(if ordered { Order::Keep } else { Order::Optimize });
+
+    if order == Order::Optimize
+    {
+        let (optimized, report) = ordering::optimize_order(curves);
+        curves = optimized;
+
+        if verbose
+        {
+            eprintln!(
+                "pen up travel: {:.3} -> {:.3} (saved {:.3})",
+                report.before,
+                report.after,
+                report.before - report.after
+            );
+        }
     }
 
-    let (canvas_x, canvas_y) = (canvas_x + offset_x * max_width, canvas_y + offset_y * max_height);
-    let (width, height) = (width * max_width, height * max_height);
+    let time_to_draw: f64 = curves.iter().map(|curve|
+    {
+        // closed curves move back to their start at the end
+        let moves = curve.len() + curve.is_closed() as usize;
+
+        moves as f64 * (delay / 2.0) + delay * 2.0
+    }).sum();
 
     if !show_area
     {
@@ -451,7 +1065,6 @@ fn main()
     };
 
     let device_state = DeviceState::new();
-    let map_point = |pos: Pos| Pos::new(canvas_x + pos.x * width, canvas_y + pos.y * height);
     if !show_area
     {
         for curve in curves
@@ -477,7 +1090,8 @@ fn main()
                 }
             }
 
-            line_drawer.draw_curve(curve.into_iter().map(map_point));
+            let closed = curve.is_closed();
+            line_drawer.draw_curve(curve.into_iter().map(map_point), closed);
         }
     } else
     {
@@ -491,15 +1105,15 @@ fn main()
             line_drawer.mouse_move(map_point(current_pos));
             current_pos = current_pos + moving_direction;
 
-            if current_pos.x > 1.0
+            if current_pos.x > width
             {
-                current_pos.x = 1.0;
+                current_pos.x = width;
                 moving_direction = Pos::new(0.0, speed);
             }
 
-            if current_pos.y > 1.0
+            if current_pos.y > height
             {
-                current_pos.y = 1.0;
+                current_pos.y = height;
                 moving_direction = Pos::new(-speed, 0.0);
             }
 
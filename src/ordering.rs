@@ -0,0 +1,224 @@
+use std::str::FromStr;
+
+use crate::contour::{Curve, Pos};
+
+
+// 2-opt only looks this far ahead so huge amounts of dots dont take forever
+const WINDOW: usize = 500;
+const MAX_PASSES: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order
+{
+    Optimize,
+    Keep
+}
+
+impl FromStr for Order
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        match s.to_lowercase().as_str()
+        {
+            "optimize" => Ok(Self::Optimize),
+            "keep" => Ok(Self::Keep),
+            _ => Err(format!("unknown order: {s}"))
+        }
+    }
+}
+
+pub struct OrderReport
+{
+    pub before: f64,
+    pub after: f64
+}
+
+fn distance(a: Pos, b: Pos) -> f64
+{
+    (a - b).magnitude()
+}
+
+// distance the pen travels in the air between curves
+pub fn pen_up_travel(curves: &[Curve]) -> f64
+{
+    curves.windows(2).map(|pair| distance(pair[0].end(), pair[1].start())).sum()
+}
+
+fn nearest_neighbor(curves: Vec<Curve>) -> Vec<Curve>
+{
+    let mut remaining = curves.into_iter().map(Some).collect::<Vec<_>>();
+    let mut ordered: Vec<Curve> = Vec::with_capacity(remaining.len());
+
+    let mut current = remaining.first().and_then(|curve| curve.as_ref().map(|curve| curve.start()));
+
+    while let Some(position) = current
+    {
+        let closest = remaining.iter().enumerate().filter_map(|(index, curve)|
+        {
+            let curve = curve.as_ref()?;
+
+            let forward = distance(position, curve.start());
+            let backward = distance(position, curve.end());
+
+            Some((index, forward.min(backward), backward < forward))
+        }).min_by(|a, b| a.1.total_cmp(&b.1));
+
+        current = closest.map(|(index, _, reverse)|
+        {
+            let mut curve = remaining[index].take().unwrap();
+            if reverse
+            {
+                curve.reverse();
+            }
+
+            let end = curve.end();
+            ordered.push(curve);
+
+            end
+        });
+    }
+
+    ordered
+}
+
+// reversing a run of curves also flips the direction each of them is drawn in
+fn two_opt(curves: &mut [Curve])
+{
+    let amount = curves.len();
+
+    for _ in 0..MAX_PASSES
+    {
+        let mut improved = false;
+
+        for i in 0..amount.saturating_sub(2)
+        {
+            for j in (i + 2)..amount.min(i + WINDOW)
+            {
+                let (end, next_start) = (curves[i].end(), curves[i + 1].start());
+                let run_end = curves[j].end();
+
+                let mut before = distance(end, next_start);
+                let mut after = distance(end, run_end);
+
+                if let Some(after_run) = curves.get(j + 1)
+                {
+                    before += distance(run_end, after_run.start());
+                    after += distance(next_start, after_run.start());
+                }
+
+                if before - after > 1e-9
+                {
+                    curves[i + 1..=j].reverse();
+                    curves[i + 1..=j].iter_mut().for_each(|curve| curve.reverse());
+
+                    improved = true;
+                }
+            }
+        }
+
+        if !improved
+        {
+            break;
+        }
+    }
+}
+
+// closed curves can be drawn starting anywhere along them, each one gets opened where it passes
+// closest to the end of the curve before it when that makes the jumps in and out shorter
+fn enter_closed(curves: &mut [Curve])
+{
+    for index in 1..curves.len()
+    {
+        if !curves[index].is_closed()
+        {
+            continue;
+        }
+
+        let pen = curves[index - 1].end();
+        let next = curves.get(index + 1).map(|curve| curve.start());
+
+        let travel = |start: Pos| distance(pen, start) + next.map(|next| distance(start, next)).unwrap_or(0.0);
+
+        let curve = &curves[index];
+        let (segment, nearest) = curve.nearest_point(pen);
+
+        let following = (segment + 1) % curve.len();
+        let entry = if distance(nearest, curve[segment]) <= distance(nearest, curve[following])
+        {
+            segment
+        } else
+        {
+            following
+        };
+
+        if travel(curve[entry]) < travel(curve.start())
+        {
+            // the opened curve ends on its start again, which the closed one doesnt repeat
+            let mut points = curve.split_at(entry).0.into_iter().collect::<Vec<_>>();
+            points.pop();
+
+            curves[index] = Curve::new_closed(points);
+        }
+    }
+}
+
+// picks the order and direction of curves to keep the pen up travel short
+pub fn optimize_order(curves: Vec<Curve>) -> (Vec<Curve>, OrderReport)
+{
+    let before = pen_up_travel(&curves);
+
+    let mut curves = nearest_neighbor(curves);
+    two_opt(&mut curves);
+    enter_closed(&mut curves);
+
+    let after = pen_up_travel(&curves);
+
+    (curves, OrderReport{before, after})
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn square_from(corners: [(f64, f64); 4]) -> Curve
+    {
+        Curve::new_closed(corners.into_iter().map(|(x, y)| Pos::new(x, y)).collect())
+    }
+
+    #[test]
+    fn closed_curves_are_entered_near_the_pen()
+    {
+        let mut curves = vec![
+            Curve::new(vec![Pos::new(0.0, 0.0), Pos::new(1.0, 0.0)]),
+            square_from([(3.0, 1.0), (2.0, 1.0), (2.0, 0.0), (3.0, 0.0)])
+        ];
+
+        let before = pen_up_travel(&curves);
+        enter_closed(&mut curves);
+
+        assert_eq!(curves[1].start(), Pos::new(2.0, 0.0));
+        assert!(curves[1].is_closed());
+        assert_eq!(curves[1].len(), 4);
+        assert!(pen_up_travel(&curves) < before);
+    }
+
+    #[test]
+    fn closed_curves_keep_their_start_when_moving_it_costs_more()
+    {
+        // entering near the pen would make the jump to the last curve longer than it saves
+        let mut curves = vec![
+            Curve::new(vec![Pos::new(0.0, 0.0), Pos::new(1.9, 0.0)]),
+            square_from([(2.0, 5.0), (2.0, 0.0), (2.1, 0.0), (2.1, 5.0)]),
+            Curve::new(vec![Pos::new(2.0, 5.5), Pos::new(0.0, 5.5)])
+        ];
+
+        let before = pen_up_travel(&curves);
+        enter_closed(&mut curves);
+
+        assert_eq!(curves[1].start(), Pos::new(2.0, 5.0));
+        assert_eq!(pen_up_travel(&curves), before);
+    }
+}
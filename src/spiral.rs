@@ -0,0 +1,55 @@
+use std::f64::consts::TAU;
+
+use crate::{
+    FloatImage,
+    contour::{Curve, Pos}
+};
+
+
+// archimedean spiral from the center outwards, the line wobbles
+// more the darker the image is under it
+pub fn spiral(image: &FloatImage, spacing: f64, amplitude: f64) -> Curve
+{
+    if spacing <= 0.0
+    {
+        panic!("invalid spiral spacing");
+    }
+
+    let (width, height) = (image.width() as f64, image.height() as f64);
+    let center = Pos::new(width / 2.0, height / 2.0);
+    let max_radius = width.min(height) / 2.0;
+
+    // one wobble per turn spacing, sampled 4 times each
+    let wavelength = spacing;
+    let step = wavelength / 4.0;
+
+    let mut points = Vec::new();
+
+    let mut angle: f64 = 0.0;
+    let mut wobble_phase: f64 = 0.0;
+    loop
+    {
+        let radius = spacing * angle / TAU;
+        if radius > max_radius
+        {
+            break;
+        }
+
+        let direction = Pos::new(angle.cos(), angle.sin());
+
+        let x = center.x + direction.x * radius;
+        let y = center.y + direction.y * radius;
+
+        let offset = amplitude * image.darkness(x, y) * wobble_phase.sin();
+
+        let x = x + direction.x * offset;
+        let y = y + direction.y * offset;
+
+        points.push(image.normalized(x, y));
+
+        angle += step / radius.max(spacing);
+        wobble_phase += TAU * step / wavelength;
+    }
+
+    Curve::new(points)
+}
@@ -0,0 +1,166 @@
+use crate::{
+    FloatImage,
+    contour::{Curve, Pos}
+};
+
+
+// tiny xorshift so the output doesnt change between runs
+pub struct Random
+{
+    state: u64
+}
+
+impl Random
+{
+    pub fn new(seed: u64) -> Self
+    {
+        Self{state: seed.max(1)}
+    }
+
+    pub fn next_u64(&mut self) -> u64
+    {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+
+        self.state
+    }
+
+    // uniform in 0..1
+    pub fn next_f64(&mut self) -> f64
+    {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+pub struct PointGrid
+{
+    cells: Vec<Vec<Pos>>,
+    cell_size: f64,
+    width: usize,
+    height: usize
+}
+
+impl PointGrid
+{
+    pub fn new(width: f64, height: f64, cell_size: f64) -> Self
+    {
+        let width = (width / cell_size).ceil() as usize + 1;
+        let height = (height / cell_size).ceil() as usize + 1;
+
+        Self{cells: vec![Vec::new(); width * height], cell_size, width, height}
+    }
+
+    fn cell_of(&self, pos: Pos) -> (usize, usize)
+    {
+        let x = ((pos.x / self.cell_size) as usize).min(self.width - 1);
+        let y = ((pos.y / self.cell_size) as usize).min(self.height - 1);
+
+        (x, y)
+    }
+
+    pub fn insert(&mut self, pos: Pos)
+    {
+        let (x, y) = self.cell_of(pos);
+
+        self.cells[y * self.width + x].push(pos);
+    }
+
+    pub fn any_within(&self, pos: Pos, radius: f64) -> bool
+    {
+        let (x, y) = self.cell_of(pos);
+        let reach = (radius / self.cell_size).ceil() as usize;
+
+        let x_range = x.saturating_sub(reach)..=(x + reach).min(self.width - 1);
+        let y_range = y.saturating_sub(reach)..=(y + reach).min(self.height - 1);
+
+        y_range.flat_map(|y| x_range.clone().map(move |x| (x, y))).any(|(x, y)|
+        {
+            self.cells[y * self.width + x].iter().any(|other|
+            {
+                (*other - pos).magnitude() < radius
+            })
+        })
+    }
+}
+
+// lighter pixels than this dont get any dots
+const MINIMUM_DARKNESS: f64 = 0.05;
+
+// blue noise dart throwing, dots get closer together the darker the image is
+// returns positions in pixel coordinates
+pub fn stipple_points(image: &FloatImage, max_points: usize) -> Vec<Pos>
+{
+    if max_points == 0
+    {
+        return Vec::new();
+    }
+
+    let (width, height) = (image.width() as f64, image.height() as f64);
+
+    let total_darkness: f64 = image.data().iter().map(|pixel| 1.0 - pixel)
+        .filter(|darkness| *darkness > MINIMUM_DARKNESS)
+        .sum();
+
+    // maximal poisson disk sampling covers around 70% of r^2 per point
+    let base_radius = (0.7 * total_darkness / max_points as f64).sqrt().max(0.5);
+
+    let mut grid = PointGrid::new(width, height, base_radius);
+    let mut random = Random::new(0x5eed_d075);
+
+    let mut points = Vec::new();
+
+    let max_failures = max_points.saturating_mul(50);
+    let mut failures = 0;
+    while points.len() < max_points && failures < max_failures
+    {
+        let pos = Pos::new(random.next_f64() * width, random.next_f64() * height);
+        let darkness = image.darkness(pos.x, pos.y);
+
+        if darkness <= MINIMUM_DARKNESS || random.next_f64() > darkness
+        {
+            failures += 1;
+            continue;
+        }
+
+        let radius = base_radius / darkness.sqrt();
+        if grid.any_within(pos, radius)
+        {
+            failures += 1;
+            continue;
+        }
+
+        failures = 0;
+
+        grid.insert(pos);
+        points.push(pos);
+    }
+
+    // serpentine rows so the pen doesnt jump across the whole canvas
+    let band = base_radius * 2.0;
+    points.sort_by(|a, b|
+    {
+        let (a_band, b_band) = ((a.y / band) as usize, (b.y / band) as usize);
+
+        a_band.cmp(&b_band).then_with(||
+        {
+            if a_band % 2 == 0
+            {
+                a.x.total_cmp(&b.x)
+            } else
+            {
+                b.x.total_cmp(&a.x)
+            }
+        })
+    });
+
+    points
+}
+
+pub fn stipple(image: &FloatImage, max_points: usize) -> Vec<Curve>
+{
+    stipple_points(image, max_points).into_iter().map(|pos|
+    {
+        Curve::new(vec![image.normalized(pos.x, pos.y)])
+    }).collect()
+}
@@ -0,0 +1,29 @@
+use std::{
+    fs,
+    io
+};
+
+use crate::contour::bezier::BezierPath;
+
+
+// paths are in curve coordinates, the longer image side gets scaled back to its pixels
+pub fn save(filename: &str, paths: &[BezierPath], width: usize, height: usize) -> io::Result<()>
+{
+    let scale = width.max(height) as f64;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n"
+    );
+
+    for path in paths
+    {
+        svg.push_str(&format!(
+            "  <path d=\"{}\" fill=\"none\" stroke=\"black\"/>\n",
+            path.svg_data(scale)
+        ));
+    }
+
+    svg.push_str("</svg>\n");
+
+    fs::write(filename, svg)
+}
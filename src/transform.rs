@@ -0,0 +1,337 @@
+use std::str::FromStr;
+
+use crate::contour::Pos;
+
+
+// x' = a * x + c * y + e, y' = b * x + d * y + f
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform
+{
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    e: f64,
+    f: f64
+}
+
+impl Transform
+{
+    pub fn identity() -> Self
+    {
+        Self{a: 1.0, b: 0.0, c: 0.0, d: 1.0, e: 0.0, f: 0.0}
+    }
+
+    pub fn translate(offset: Pos) -> Self
+    {
+        Self{e: offset.x, f: offset.y, ..Self::identity()}
+    }
+
+    pub fn scale(x: f64, y: f64) -> Self
+    {
+        Self{a: x, d: y, ..Self::identity()}
+    }
+
+    // clockwise on screen since y points down
+    pub fn rotate(angle: f64) -> Self
+    {
+        let (sin, cos) = angle.sin_cos();
+
+        Self{a: cos, b: sin, c: -sin, d: cos, ..Self::identity()}
+    }
+
+    // this transform followed by the other one
+    pub fn then(self, other: Self) -> Self
+    {
+        Self{
+            a: other.a * self.a + other.c * self.b,
+            b: other.b * self.a + other.d * self.b,
+            c: other.a * self.c + other.c * self.d,
+            d: other.b * self.c + other.d * self.d,
+            e: other.a * self.e + other.c * self.f + other.e,
+            f: other.b * self.e + other.d * self.f + other.f
+        }
+    }
+
+    pub fn apply(&self, pos: Pos) -> Pos
+    {
+        Pos::new(self.a * pos.x + self.c * pos.y + self.e, self.b * pos.x + self.d * pos.y + self.f)
+    }
+
+    // none for transforms that squash everything onto a line
+    pub fn inverse(&self) -> Option<Self>
+    {
+        let determinant = self.a * self.d - self.b * self.c;
+        if determinant.abs() < f64::EPSILON
+        {
+            return None;
+        }
+
+        let (a, b, c, d) = (self.d / determinant, -self.b / determinant, -self.c / determinant, self.a / determinant);
+
+        Some(Self{a, b, c, d, e: -(a * self.e + c * self.f), f: -(b * self.e + d * self.f)})
+    }
+
+    // how much the transform stretches lengths along whichever axis it stretches most
+    pub fn max_scale(&self) -> f64
+    {
+        self.a.hypot(self.b).max(self.c.hypot(self.d))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fit
+{
+    Contain,
+    Cover,
+    Stretch
+}
+
+impl FromStr for Fit
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        match s.to_lowercase().as_str()
+        {
+            "contain" => Ok(Self::Contain),
+            "cover" => Ok(Self::Cover),
+            "stretch" => Ok(Self::Stretch),
+            _ => Err(format!("unknown fit: {s}"))
+        }
+    }
+}
+
+// where the drawing sits in the canvas as fractions of the space left around it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Anchor
+{
+    x: f64,
+    y: f64
+}
+
+impl FromStr for Anchor
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        let (x, y) = match s.to_lowercase().as_str()
+        {
+            "center" => (0.5, 0.5),
+            "top-left" => (0.0, 0.0),
+            "top" => (0.5, 0.0),
+            "top-right" => (1.0, 0.0),
+            "left" => (0.0, 0.5),
+            "right" => (1.0, 0.5),
+            "bottom-left" => (0.0, 1.0),
+            "bottom" => (0.5, 1.0),
+            "bottom-right" => (1.0, 1.0),
+            _ => return Err(format!("unknown anchor: {s}"))
+        };
+
+        Ok(Self{x, y})
+    }
+}
+
+impl Default for Anchor
+{
+    fn default() -> Self
+    {
+        Self{x: 0.5, y: 0.5}
+    }
+}
+
+pub struct Placement
+{
+    // radians
+    pub rotation: f64,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    // relative to the size the fit picks
+    pub scale: f64,
+    pub anchor: Anchor,
+    // fraction of the shorter canvas side left empty on every side
+    pub margin: f64,
+    pub fit: Fit
+}
+
+impl Placement
+{
+    // a scale of 0 draws nothing, a negative margin reaches past the canvas and
+    // a margin of half the shorter side leaves nothing to draw in
+    pub fn check(&self) -> Result<(), String>
+    {
+        if self.scale <= 0.0
+        {
+            return Err(format!("the scale has to be above 0, got {}", self.scale));
+        }
+
+        if !(0.0..0.5).contains(&self.margin)
+        {
+            return Err(format!("the margin has to be at least 0 and below 0.5, got {}", self.margin));
+        }
+
+        Ok(())
+    }
+
+    // the area inside the margins of a canvas of this size
+    pub fn area(&self, canvas: Pos) -> (Pos, Pos)
+    {
+        let margin = self.margin * canvas.x.min(canvas.y);
+
+        (Pos::new(margin, margin), canvas - Pos::new(margin, margin))
+    }
+
+    // maps the size sized content starting at the origin into a canvas starting at the origin
+    pub fn transform(&self, size: Pos, canvas: Pos) -> Transform
+    {
+        let flip = Transform::scale(
+            if self.flip_x { -1.0 } else { 1.0 },
+            if self.flip_y { -1.0 } else { 1.0 }
+        );
+
+        let oriented = Transform::translate(-(size * 0.5)).then(flip).then(Transform::rotate(self.rotation));
+
+        // size of the box around the content once its turned
+        let corners = [Pos::new(0.0, 0.0), Pos::new(size.x, 0.0), Pos::new(0.0, size.y), size]
+            .map(|corner| oriented.apply(corner));
+
+        let extent = |axis: fn(&Pos) -> f64|
+        {
+            let values = corners.iter().map(axis);
+
+            values.clone().fold(f64::MIN, f64::max) - values.fold(f64::MAX, f64::min)
+        };
+
+        let turned = Pos::new(extent(|pos| pos.x), extent(|pos| pos.y));
+
+        let (low, high) = self.area(canvas);
+        let available = high - low;
+
+        let (fit_x, fit_y) = (available.x / turned.x, available.y / turned.y);
+        let (scale_x, scale_y) = match self.fit
+        {
+            Fit::Contain => (fit_x.min(fit_y), fit_x.min(fit_y)),
+            Fit::Cover => (fit_x.max(fit_y), fit_x.max(fit_y)),
+            Fit::Stretch => (fit_x, fit_y)
+        };
+
+        let (scale_x, scale_y) = (scale_x * self.scale, scale_y * self.scale);
+        let placed = Pos::new(turned.x * scale_x, turned.y * scale_y);
+
+        // whats left over (or cropped off when its negative) gets split by the anchor
+        let center = low
+            + Pos::new((available.x - placed.x) * self.anchor.x, (available.y - placed.y) * self.anchor.y)
+            + placed * 0.5;
+
+        oriented.then(Transform::scale(scale_x, scale_y)).then(Transform::translate(center))
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn placement(fit: Fit) -> Placement
+    {
+        Placement{
+            rotation: 0.0,
+            flip_x: false,
+            flip_y: false,
+            scale: 1.0,
+            anchor: Anchor::default(),
+            margin: 0.0,
+            fit
+        }
+    }
+
+    fn close(a: Pos, b: Pos) -> bool
+    {
+        (a - b).magnitude() < 1e-9
+    }
+
+    #[test]
+    fn contain_centers_the_drawing()
+    {
+        let transform = placement(Fit::Contain).transform(Pos::new(1.0, 0.5), Pos::new(200.0, 200.0));
+
+        assert!(close(transform.apply(Pos::new(0.0, 0.0)), Pos::new(0.0, 50.0)));
+        assert!(close(transform.apply(Pos::new(1.0, 0.5)), Pos::new(200.0, 150.0)));
+        assert!((transform.max_scale() - 200.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cover_fills_from_the_anchor()
+    {
+        let mut placement = placement(Fit::Cover);
+        placement.anchor = "top-left".parse().unwrap();
+
+        let transform = placement.transform(Pos::new(1.0, 0.5), Pos::new(200.0, 200.0));
+
+        assert!(close(transform.apply(Pos::new(0.0, 0.0)), Pos::new(0.0, 0.0)));
+        assert!(close(transform.apply(Pos::new(1.0, 0.5)), Pos::new(400.0, 200.0)));
+    }
+
+    #[test]
+    fn quarter_turn_swaps_the_sides()
+    {
+        let mut placement = placement(Fit::Contain);
+        placement.rotation = std::f64::consts::FRAC_PI_2;
+
+        let transform = placement.transform(Pos::new(1.0, 0.5), Pos::new(100.0, 200.0));
+
+        // clockwise, so the top left corner ends up at the top right
+        assert!(close(transform.apply(Pos::new(0.0, 0.0)), Pos::new(100.0, 0.0)));
+        assert!(close(transform.apply(Pos::new(1.0, 0.5)), Pos::new(0.0, 200.0)));
+    }
+
+    #[test]
+    fn stretch_with_margin_and_flip()
+    {
+        let mut placement = placement(Fit::Stretch);
+        placement.margin = 0.1;
+        placement.flip_x = true;
+
+        let canvas = Pos::new(300.0, 100.0);
+        assert_eq!(placement.area(canvas), (Pos::new(10.0, 10.0), Pos::new(290.0, 90.0)));
+
+        let transform = placement.transform(Pos::new(1.0, 0.5), canvas);
+
+        assert!(close(transform.apply(Pos::new(0.0, 0.0)), Pos::new(290.0, 10.0)));
+        assert!(close(transform.apply(Pos::new(1.0, 0.5)), Pos::new(10.0, 90.0)));
+    }
+
+    #[test]
+    fn check_rejects_scales_and_margins_without_room()
+    {
+        assert!(placement(Fit::Contain).check().is_ok());
+
+        for (scale, margin) in [(0.0, 0.0), (-1.0, 0.0), (1.0, 0.5), (1.0, 0.7), (1.0, -0.1)]
+        {
+            let mut placement = placement(Fit::Contain);
+            placement.scale = scale;
+            placement.margin = margin;
+
+            assert!(placement.check().is_err(), "scale {scale} margin {margin}");
+        }
+
+        let mut placement = placement(Fit::Contain);
+        placement.margin = 0.49;
+        assert!(placement.check().is_ok());
+    }
+
+    #[test]
+    fn inverse_undoes_the_transform()
+    {
+        let transform = Transform::rotate(0.3).then(Transform::scale(2.0, 3.0)).then(Transform::translate(Pos::new(5.0, -1.0)));
+        let inverse = transform.inverse().unwrap();
+
+        let pos = Pos::new(0.25, 0.75);
+        assert!(close(inverse.apply(transform.apply(pos)), pos));
+
+        assert!(Transform::scale(0.0, 1.0).inverse().is_none());
+    }
+}
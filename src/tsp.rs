@@ -0,0 +1,292 @@
+use crate::contour::Pos;
+
+
+// how many of the closest points are considered for each 2-opt move
+const NEIGHBORS_AMOUNT: usize = 8;
+const MAX_PASSES: usize = 50;
+
+fn distance(a: Pos, b: Pos) -> f64
+{
+    (a - b).magnitude()
+}
+
+// indices of the points bucketed into square cells, so finding the closest ones
+// only looks at the cells around a point instead of every other point
+struct IndexGrid<'a>
+{
+    points: &'a [Pos],
+    cells: Vec<Vec<usize>>,
+    low: Pos,
+    cell_size: f64,
+    width: usize,
+    height: usize
+}
+
+impl<'a> IndexGrid<'a>
+{
+    pub fn new(points: &'a [Pos]) -> Self
+    {
+        let low = points.iter().fold(points[0], |low, point| Pos::new(low.x.min(point.x), low.y.min(point.y)));
+        let high = points.iter().fold(points[0], |high, point| Pos::new(high.x.max(point.x), high.y.max(point.y)));
+
+        // around 4 points in every cell, points on a line still get spread over many cells
+        let size = high - low;
+        let amount = points.len() as f64;
+        let cell_size = ((size.x * size.y / amount).sqrt().max(size.x.max(size.y) / amount) * 2.0).max(1e-9);
+
+        let width = (size.x / cell_size) as usize + 1;
+        let height = (size.y / cell_size) as usize + 1;
+
+        let mut grid = Self{points, cells: vec![Vec::new(); width * height], low, cell_size, width, height};
+        for (index, point) in points.iter().enumerate()
+        {
+            let cell = grid.cell_index(*point);
+            grid.cells[cell].push(index);
+        }
+
+        grid
+    }
+
+    fn cell_of(&self, pos: Pos) -> (usize, usize)
+    {
+        let x = (((pos.x - self.low.x) / self.cell_size).max(0.0) as usize).min(self.width - 1);
+        let y = (((pos.y - self.low.y) / self.cell_size).max(0.0) as usize).min(self.height - 1);
+
+        (x, y)
+    }
+
+    fn cell_index(&self, pos: Pos) -> usize
+    {
+        let (x, y) = self.cell_of(pos);
+
+        y * self.width + x
+    }
+
+    pub fn remove(&mut self, index: usize)
+    {
+        let cell = self.cell_index(self.points[index]);
+
+        if let Some(position) = self.cells[cell].iter().position(|other| *other == index)
+        {
+            self.cells[cell].swap_remove(position);
+        }
+    }
+
+    // every index in the cells exactly ring cells away from the cell at x, y
+    fn ring(&self, (x, y): (usize, usize), ring: usize) -> impl Iterator<Item=usize> + '_
+    {
+        let (x, y, ring) = (x as i64, y as i64, ring as i64);
+
+        // the top and bottom rows would both be the center cell in ring 0
+        let rows = (-ring..=ring).flat_map(move |d| [(d, -ring), (d, ring)])
+            .take(if ring == 0 { 1 } else { usize::MAX });
+        let columns = (1 - ring..ring).flat_map(move |d| [(-ring, d), (ring, d)]);
+
+        rows.chain(columns).map(move |(dx, dy)| (x + dx, y + dy))
+            .filter(|(x, y)| (0..self.width as i64).contains(x) && (0..self.height as i64).contains(y))
+            .flat_map(|(x, y)| self.cells[y as usize * self.width + x as usize].iter().copied())
+    }
+
+    // up to amount indices closest to pos, closest first, without skip
+    pub fn closest(&self, pos: Pos, amount: usize, skip: Option<usize>) -> Vec<usize>
+    {
+        let center = self.cell_of(pos);
+
+        let mut found: Vec<(f64, usize)> = Vec::new();
+        for ring in 0..=self.width.max(self.height)
+        {
+            found.extend(self.ring(center, ring).filter(|index| Some(*index) != skip).map(|index|
+            {
+                (distance(pos, self.points[index]), index)
+            }));
+
+            found.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+            found.truncate(amount);
+
+            // anything in the rings further out is at least this far away
+            let reach = ring as f64 * self.cell_size;
+            if found.len() == amount && found.last().map(|(distance, _)| *distance <= reach).unwrap_or(true)
+            {
+                break;
+            }
+        }
+
+        found.into_iter().map(|(_, index)| index).collect()
+    }
+}
+
+fn nearest_neighbor_order(points: &[Pos]) -> Vec<usize>
+{
+    let mut grid = IndexGrid::new(points);
+    let mut order = Vec::with_capacity(points.len());
+
+    let mut current = 0;
+    grid.remove(current);
+    order.push(current);
+
+    for _ in 1..points.len()
+    {
+        let next = grid.closest(points[current], 1, None)[0];
+
+        grid.remove(next);
+        order.push(next);
+
+        current = next;
+    }
+
+    order
+}
+
+fn closest_neighbors(points: &[Pos]) -> Vec<Vec<usize>>
+{
+    let grid = IndexGrid::new(points);
+
+    (0..points.len()).map(|index|
+    {
+        grid.closest(points[index], NEIGHBORS_AMOUNT, Some(index))
+    }).collect()
+}
+
+// 2-opt for an open path, every improving move reverses a part of the tour
+fn two_opt(points: &[Pos], order: &mut [usize])
+{
+    let neighbors = closest_neighbors(points);
+
+    let mut positions = vec![0; order.len()];
+    for (position, index) in order.iter().enumerate()
+    {
+        positions[*index] = position;
+    }
+
+    let last = order.len() - 1;
+    let d = |a: usize, b: usize| distance(points[a], points[b]);
+
+    for _ in 0..MAX_PASSES
+    {
+        let mut improved = false;
+
+        for i in 0..last
+        {
+            for &other in &neighbors[order[i]]
+            {
+                // connects the current point with its neighbor directly
+                let j = positions[other];
+                let (low, high) = if j > i { (i, j) } else { (j, i) };
+
+                if high <= low + 1
+                {
+                    continue;
+                }
+
+                let (a, b, c) = (order[low], order[low + 1], order[high]);
+
+                let gain = if high == last
+                {
+                    d(a, b) - d(a, c)
+                } else
+                {
+                    let next = order[high + 1];
+
+                    d(a, b) + d(c, next) - d(a, c) - d(b, next)
+                };
+
+                if gain > 1e-9
+                {
+                    order[low + 1..=high].reverse();
+                    for position in low + 1..=high
+                    {
+                        positions[order[position]] = position;
+                    }
+
+                    improved = true;
+                    break;
+                }
+            }
+        }
+
+        if !improved
+        {
+            break;
+        }
+    }
+}
+
+// visits every point once, trying to keep the total path short
+pub fn tour(points: Vec<Pos>) -> Vec<Pos>
+{
+    if points.len() < 3
+    {
+        return points;
+    }
+
+    let mut order = nearest_neighbor_order(&points);
+    two_opt(&points, &mut order);
+
+    order.into_iter().map(|index| points[index]).collect()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::stipple::Random;
+
+    // a 10x10 grid in a scrambled order
+    fn scrambled_grid() -> Vec<Pos>
+    {
+        (0..100).map(|index| (index * 37) % 100).map(|index|
+        {
+            Pos::new((index % 10) as f64, (index / 10) as f64)
+        }).collect()
+    }
+
+    fn path_length(points: &[Pos]) -> f64
+    {
+        points.windows(2).map(|pair| distance(pair[0], pair[1])).sum()
+    }
+
+    #[test]
+    fn grid_finds_the_same_neighbors_as_a_full_scan()
+    {
+        let mut random = Random::new(7);
+        let points = (0..500).map(|_| Pos::new(random.next_f64() * 300.0, random.next_f64() * 100.0))
+            .collect::<Vec<_>>();
+
+        let grid = IndexGrid::new(&points);
+
+        for (index, point) in points.iter().enumerate().step_by(17)
+        {
+            let mut expected = (0..points.len()).filter(|other| *other != index).collect::<Vec<_>>();
+            expected.sort_by(|a, b|
+            {
+                distance(*point, points[*a]).total_cmp(&distance(*point, points[*b])).then(a.cmp(b))
+            });
+            expected.truncate(NEIGHBORS_AMOUNT);
+
+            assert_eq!(grid.closest(*point, NEIGHBORS_AMOUNT, Some(index)), expected);
+        }
+    }
+
+    #[test]
+    fn tour_visits_every_point_once()
+    {
+        let points = scrambled_grid();
+        let visited = tour(points.clone());
+
+        assert_eq!(visited.len(), points.len());
+        assert!(points.iter().all(|point| visited.iter().any(|other| other.x == point.x && other.y == point.y)));
+    }
+
+    #[test]
+    fn tour_is_close_to_the_shortest()
+    {
+        let points = scrambled_grid();
+
+        let greedy = nearest_neighbor_order(&points).into_iter().map(|index| points[index]).collect::<Vec<_>>();
+        let length = path_length(&tour(points));
+
+        // a serpentine through the grid is 99 long
+        assert!(length <= path_length(&greedy));
+        assert!(length < 99.0 * 1.15, "{length}");
+    }
+}